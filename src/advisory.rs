@@ -0,0 +1,454 @@
+//! Structured security advisories, and exporting them for vulnerability databases
+
+use std::fmt::Write;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use crate::{Changelog, Entry, Scheme, Version};
+
+/// Details of a security advisory, attached to a security entry
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Advisory {
+    /// Advisory identifier, e.g. `GHSA-xxxx-xxxx-xxxx`, `CVE-2025-1234` or `RUSTSEC-2025-0001`
+    pub id: String,
+    /// Other identifiers for the same vulnerability
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// How severe the vulnerability is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Affected version ranges, e.g. `>= 1.0.0, < 1.2.3`
+    ///
+    /// Defaults to every version before the one the entry is released in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affected: Vec<String>,
+    /// People or organisations credited with reporting or fixing the vulnerability
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub credit: Vec<String>,
+    /// Link to the advisory, if it can't be derived from the identifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Severity of a vulnerability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    /// Low impact
+    Low,
+    /// Moderate impact
+    Medium,
+    /// Serious impact
+    High,
+    /// Severe impact, fix immediately
+    Critical,
+}
+
+impl Severity {
+    /// The lowercase name of this severity
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Low => "yellowgreen",
+            Severity::Medium => "yellow",
+            Severity::High => "orange",
+            Severity::Critical => "red",
+        }
+    }
+}
+
+/// Link to a well known advisory database entry for an identifier
+fn id_url(id: &str) -> Option<String> {
+    if id.starts_with("GHSA-") {
+        Some(format!("https://github.com/advisories/{}", id))
+    } else if id.starts_with("CVE-") {
+        Some(format!("https://www.cve.org/CVERecord?id={}", id))
+    } else if id.starts_with("RUSTSEC-") {
+        Some(format!("https://rustsec.org/advisories/{}.html", id))
+    } else {
+        None
+    }
+}
+
+impl Advisory {
+    /// Link to this advisory, explicitly set or derived from its identifier
+    pub fn link(&self) -> Option<String> {
+        self.url.clone().or_else(|| id_url(&self.id))
+    }
+
+    /// Write a security entry's text decorated with this advisory's details
+    pub(crate) fn write_markdown(&self, f: &mut impl Write, text: &str) -> std::fmt::Result {
+        if let Some(severity) = self.severity {
+            write!(
+                f,
+                "![severity: {0}](https://img.shields.io/badge/severity-{0}-{1}) ",
+                severity.name(),
+                severity.color()
+            )?;
+        }
+        write!(f, "{}", text.trim_end())?;
+
+        let ids = std::iter::once((&self.id, self.link()))
+            .chain(self.aliases.iter().map(|alias| (alias, id_url(alias))))
+            .map(|(id, url)| match url {
+                Some(url) => format!("[{}]({})", id, url),
+                None => id.clone(),
+            })
+            .collect::<Vec<_>>();
+        write!(f, " ({})", ids.join(", "))?;
+
+        if !self.affected.is_empty() {
+            write!(f, ". Affected versions: `{}`", self.affected.join("`, `"))?;
+        }
+        if !self.credit.is_empty() {
+            write!(f, ". Credit: {}", self.credit.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The package advisories are published for
+#[derive(Debug, Clone)]
+pub struct Package {
+    /// Package name
+    pub name: String,
+    /// Package ecosystem as named by OSV, e.g. `crates.io` or `npm`
+    pub ecosystem: String,
+}
+
+/// A security advisory fixed in a released version
+#[derive(Debug, Clone, Copy)]
+pub struct Fixed<'a> {
+    /// The version the fix was released in
+    pub version: &'a Version,
+    /// The security entry describing the fix
+    pub entry: &'a Entry,
+    /// The entry's advisory details
+    pub advisory: &'a Advisory,
+    /// The scheme the version follows, `None` for free-form versions
+    pub scheme: Option<Scheme>,
+}
+
+impl Changelog {
+    /// Every security advisory fixed in a released version, newest first
+    ///
+    /// Nested security entries are included, following their parent.
+    pub fn advisories(&self) -> Vec<Fixed<'_>> {
+        fn collect<'a>(
+            version: &'a Version,
+            scheme: Option<Scheme>,
+            entries: &'a [Entry],
+            fixed: &mut Vec<Fixed<'a>>,
+        ) {
+            for entry in entries {
+                if let Some(advisory) = &entry.advisory {
+                    fixed.push(Fixed {
+                        version,
+                        entry,
+                        advisory,
+                        scheme,
+                    });
+                }
+                collect(version, scheme, &entry.children, fixed);
+            }
+        }
+
+        let mut fixed = vec![];
+        for version in self.all_versions() {
+            let scheme = self.version_number(&version.version).scheme();
+            collect(version, scheme, &version.changes.security, &mut fixed);
+        }
+        fixed
+    }
+}
+
+/// A version range as OSV events, e.g. `>= 1.0.0, < 1.2.3`
+struct Range {
+    introduced: String,
+    fixed: Option<String>,
+    last_affected: Option<String>,
+}
+
+impl Fixed<'_> {
+    fn ranges(&self) -> anyhow::Result<Vec<Range>> {
+        if self.advisory.affected.is_empty() {
+            return Ok(vec![Range {
+                introduced: "0".to_string(),
+                fixed: Some(self.version.version.clone()),
+                last_affected: None,
+            }]);
+        }
+
+        self.advisory
+            .affected
+            .iter()
+            .map(|affected| {
+                let mut range = Range {
+                    introduced: "0".to_string(),
+                    fixed: None,
+                    last_affected: None,
+                };
+                for comparator in affected.split(',').map(str::trim) {
+                    if let Some(version) = comparator.strip_prefix(">=") {
+                        range.introduced = version.trim().to_string();
+                    } else if let Some(version) = comparator.strip_prefix("<=") {
+                        range.last_affected = Some(version.trim().to_string());
+                    } else if let Some(version) = comparator.strip_prefix('<') {
+                        range.fixed = Some(version.trim().to_string());
+                    } else {
+                        bail!(
+                            "Unsupported range `{}` in advisory {}, use `>=`, `<` or `<=`",
+                            comparator,
+                            self.advisory.id
+                        );
+                    }
+                }
+                if range.fixed.is_some() && range.last_affected.is_some() {
+                    bail!(
+                        "Range `{}` in advisory {} mixes `<` and `<=` bounds, use one of them",
+                        affected,
+                        self.advisory.id
+                    );
+                }
+                if range.fixed.is_none() && range.last_affected.is_none() {
+                    range.fixed = Some(self.version.version.clone());
+                }
+                Ok(range)
+            })
+            .collect()
+    }
+
+    /// The first line of the entry, used as a summary
+    fn summary(&self) -> &str {
+        self.entry.text.lines().next().unwrap_or_default().trim()
+    }
+
+    /// Export as an [OSV](https://ossf.github.io/osv-schema/) JSON document
+    ///
+    /// Ranges are `SEMVER` ranges for semver changelogs, and `ECOSYSTEM` ranges otherwise
+    pub fn to_osv(&self, package: &Package) -> anyhow::Result<serde_json::Value> {
+        let date = format!("{}T00:00:00Z", self.version.date);
+        let range_type = match self.scheme {
+            Some(Scheme::Semver) => "SEMVER",
+            _ => "ECOSYSTEM",
+        };
+        let ranges = self
+            .ranges()?
+            .into_iter()
+            .map(|range| {
+                let mut events = vec![serde_json::json!({ "introduced": range.introduced })];
+                if let Some(fixed) = range.fixed {
+                    events.push(serde_json::json!({ "fixed": fixed }));
+                }
+                if let Some(last_affected) = range.last_affected {
+                    events.push(serde_json::json!({ "last_affected": last_affected }));
+                }
+                serde_json::json!({ "type": range_type, "events": events })
+            })
+            .collect::<Vec<_>>();
+
+        let mut osv = serde_json::json!({
+            "schema_version": "1.6.0",
+            "id": self.advisory.id,
+            "modified": date,
+            "published": date,
+            "aliases": self.advisory.aliases,
+            "summary": self.summary(),
+            "details": self.entry.text.trim(),
+            "affected": [{
+                "package": { "ecosystem": package.ecosystem, "name": package.name },
+                "ranges": ranges,
+            }],
+            "references": self.advisory.link().map(|url| {
+                vec![serde_json::json!({ "type": "ADVISORY", "url": url })]
+            }).unwrap_or_default(),
+            "credits": self.advisory.credit.iter().map(|name| {
+                serde_json::json!({ "name": name })
+            }).collect::<Vec<_>>(),
+        });
+        if let Some(severity) = self.advisory.severity {
+            osv["database_specific"] =
+                serde_json::json!({ "severity": severity.name().to_uppercase() });
+        }
+
+        Ok(osv)
+    }
+
+    /// Export as a RustSec style advisory TOML document
+    #[cfg(feature = "toml")]
+    pub fn to_rustsec(&self, package: &Package) -> anyhow::Result<String> {
+        let ranges = self.ranges()?;
+        let document = RustSec {
+            advisory: RustSecAdvisory {
+                id: &self.advisory.id,
+                package: &package.name,
+                date: &self.version.date,
+                url: self.advisory.link(),
+                aliases: &self.advisory.aliases,
+                title: self.summary(),
+                description: self.entry.text.trim(),
+            },
+            versions: RustSecVersions {
+                patched: ranges
+                    .iter()
+                    .filter_map(|range| Some(format!(">= {}", range.fixed.as_ref()?)))
+                    .collect(),
+                unaffected: ranges
+                    .iter()
+                    .filter(|range| range.introduced != "0")
+                    .map(|range| format!("< {}", range.introduced))
+                    .collect(),
+            },
+        };
+        Ok(toml::to_string(&document)?)
+    }
+}
+
+#[cfg(feature = "toml")]
+#[derive(Serialize)]
+struct RustSec<'a> {
+    advisory: RustSecAdvisory<'a>,
+    versions: RustSecVersions,
+}
+
+#[cfg(feature = "toml")]
+#[derive(Serialize)]
+struct RustSecAdvisory<'a> {
+    id: &'a str,
+    package: &'a str,
+    date: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    aliases: &'a [String],
+    title: &'a str,
+    description: &'a str,
+}
+
+#[cfg(feature = "toml")]
+#[derive(Serialize)]
+struct RustSecVersions {
+    patched: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unaffected: Vec<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn changelog() -> Changelog {
+        let advisory = Advisory {
+            id: "GHSA-abcd-efgh-ijkl".to_string(),
+            aliases: vec!["CVE-2025-1234".to_string()],
+            severity: Some(Severity::High),
+            affected: vec![">= 1.0.0, < 1.2.3".to_string()],
+            ..Default::default()
+        };
+        Changelog {
+            versions: vec![Version {
+                version: "1.2.3".to_string(),
+                tag: "v1.2.3".to_string(),
+                date: "2025-03-01".to_string(),
+                changes: crate::Changes {
+                    security: vec![
+                        "Plain entry".into(),
+                        Entry {
+                            text: "Path traversal".to_string(),
+                            advisory: Some(advisory),
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_advisory_links() {
+        let changelog = changelog();
+        let entry = &changelog.versions[0].changes.security[1];
+        assert_eq!(
+            entry.to_string(),
+            "![severity: high](https://img.shields.io/badge/severity-high-orange) Path traversal \
+             ([GHSA-abcd-efgh-ijkl](https://github.com/advisories/GHSA-abcd-efgh-ijkl), \
+             [CVE-2025-1234](https://www.cve.org/CVERecord?id=CVE-2025-1234)). \
+             Affected versions: `>= 1.0.0, < 1.2.3`"
+        );
+    }
+
+    #[test]
+    fn exports_osv_ranges() {
+        let changelog = changelog();
+        let advisories = changelog.advisories();
+        assert_eq!(advisories.len(), 1);
+
+        let package = Package {
+            name: "widget".to_string(),
+            ecosystem: "crates.io".to_string(),
+        };
+        let osv = advisories[0].to_osv(&package).unwrap();
+        assert_eq!(
+            osv["affected"][0]["ranges"][0]["events"],
+            serde_json::json!([{ "introduced": "1.0.0" }, { "fixed": "1.2.3" }])
+        );
+        assert_eq!(osv["affected"][0]["ranges"][0]["type"], "SEMVER");
+        assert_eq!(osv["database_specific"]["severity"], "HIGH");
+
+        // Other schemes are only ordered by their ecosystem
+        let calver = Changelog {
+            version_scheme: Some(Scheme::Calver),
+            versions: vec![Version {
+                version: "2025.03.1".to_string(),
+                ..changelog.versions[0].clone()
+            }],
+            ..Default::default()
+        };
+        let osv = calver.advisories()[0].to_osv(&package).unwrap();
+        assert_eq!(osv["affected"][0]["ranges"][0]["type"], "ECOSYSTEM");
+    }
+
+    #[test]
+    fn rejects_mixed_range_bounds() {
+        let mut changelog = changelog();
+        let security = &mut changelog.versions[0].changes.security;
+        security[1].advisory.as_mut().unwrap().affected = vec!["< 1.2.0, <= 1.2.2".to_string()];
+
+        let package = Package {
+            name: "widget".to_string(),
+            ecosystem: "crates.io".to_string(),
+        };
+        let err = changelog.advisories()[0].to_osv(&package).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Range `< 1.2.0, <= 1.2.2` in advisory GHSA-abcd-efgh-ijkl mixes `<` and `<=` bounds, use one of them"
+        );
+    }
+
+    #[test]
+    fn exports_nested_advisories() {
+        let mut changelog = changelog();
+        let security = &mut changelog.versions[0].changes.security;
+        let nested = security.remove(1);
+        security[0].children.push(nested);
+
+        let advisories = changelog.advisories();
+        assert_eq!(advisories.len(), 1);
+        assert_eq!(advisories[0].entry.text, "Path traversal");
+    }
+}
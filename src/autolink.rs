@@ -0,0 +1,230 @@
+//! Linking issue references, commit SHAs and @mentions in rendered Markdown
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+/// The code forge hosting a repository, which decides the shape of its links
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum Forge {
+    /// GitHub
+    Github,
+    /// GitLab, including self-hosted instances
+    Gitlab,
+    /// Bitbucket Cloud
+    Bitbucket,
+    /// Gitea and Forgejo, including Codeberg
+    Gitea,
+}
+
+impl Forge {
+    /// Guess the forge from a repository url
+    pub fn detect(repository: &str) -> Option<Forge> {
+        let host = repository
+            .split_once("://")
+            .map_or(repository, |(_, rest)| rest)
+            .split('/')
+            .next()?
+            .to_ascii_lowercase();
+        if host.contains("github") {
+            Some(Forge::Github)
+        } else if host.contains("gitlab") {
+            Some(Forge::Gitlab)
+        } else if host.contains("bitbucket") {
+            Some(Forge::Bitbucket)
+        } else if host == "codeberg.org" || host.contains("gitea") || host.contains("forgejo") {
+            Some(Forge::Gitea)
+        } else {
+            None
+        }
+    }
+
+    fn issue_url(&self, repository: &str, number: &str) -> String {
+        match self {
+            Forge::Gitlab => format!("{}/-/issues/{}", repository, number),
+            Forge::Github | Forge::Bitbucket | Forge::Gitea => {
+                format!("{}/issues/{}", repository, number)
+            }
+        }
+    }
+
+    fn commit_url(&self, repository: &str, sha: &str) -> String {
+        match self {
+            Forge::Github | Forge::Gitea => format!("{}/commit/{}", repository, sha),
+            Forge::Gitlab => format!("{}/-/commit/{}", repository, sha),
+            Forge::Bitbucket => format!("{}/commits/{}", repository, sha),
+        }
+    }
+
+    fn user_url(&self, repository: &str, user: &str) -> String {
+        let scheme_end = repository.find("://").map_or(0, |idx| idx + 3);
+        let host_end = repository[scheme_end..]
+            .find('/')
+            .map_or(repository.len(), |idx| scheme_end + idx);
+        format!("{}/{}", &repository[..host_end], user)
+    }
+}
+
+/// Link `#123`, commit SHAs and `@user` mentions to the repository on `forge`
+///
+/// Code spans, code blocks, HTML and existing links are left untouched,
+/// as are tokens escaped with a backslash.
+pub fn autolink(markdown: &str, repository: &str, forge: Forge) -> String {
+    let repository = repository.trim_end_matches('/');
+    let mut out = String::with_capacity(markdown.len());
+    let mut copied = 0;
+    let mut in_link = 0;
+    let mut in_code_block = false;
+
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link { .. } | Tag::Image { .. }) => in_link += 1,
+            Event::End(TagEnd::Link | TagEnd::Image) => in_link -= 1,
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(_) if in_link == 0 && !in_code_block && range.start >= copied => {
+                for (start, end, url) in tokens(markdown, range.start, range.end) {
+                    let token = &markdown[start..end];
+                    let url = match url {
+                        Token::Issue => forge.issue_url(repository, &token[1..]),
+                        Token::Commit => forge.commit_url(repository, token),
+                        Token::Mention => forge.user_url(repository, &token[1..]),
+                    };
+                    out.push_str(&markdown[copied..start]);
+                    out.push_str(&format!("[{}]({})", token, url));
+                    copied = end;
+                }
+            }
+            _ => {}
+        }
+    }
+    out.push_str(&markdown[copied..]);
+    out
+}
+
+enum Token {
+    Issue,
+    Commit,
+    Mention,
+}
+
+/// Find linkable tokens in `markdown[start..end]`, skipping anything that looks like a url
+fn tokens(markdown: &str, start: usize, end: usize) -> Vec<(usize, usize, Token)> {
+    let mut found = vec![];
+    let mut word_start = start;
+    for (idx, c) in markdown[start..end]
+        .char_indices()
+        .map(|(idx, c)| (start + idx, c))
+        .chain(std::iter::once((end, ' ')))
+    {
+        if !c.is_whitespace() {
+            continue;
+        }
+        let word = &markdown[word_start..idx];
+        if !word.contains("://") && !word.starts_with("www.") {
+            word_tokens(markdown, word_start, idx, &mut found);
+        }
+        word_start = idx + c.len_utf8();
+    }
+    found
+}
+
+fn word_tokens(markdown: &str, start: usize, end: usize, found: &mut Vec<(usize, usize, Token)>) {
+    let bytes = markdown.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut idx = start;
+
+    while idx < end {
+        let prev = if idx == 0 { b' ' } else { bytes[idx - 1] };
+        if is_word(prev) || matches!(prev, b'&' | b'\\' | b'/' | b'@' | b'#') {
+            idx += 1;
+            continue;
+        }
+
+        let run = |from: usize, accept: fn(u8) -> bool| {
+            let mut to = from;
+            while to < end && accept(bytes[to]) {
+                to += 1;
+            }
+            to
+        };
+        let token = match bytes[idx] {
+            b'#' => {
+                let to = run(idx + 1, |b| b.is_ascii_digit());
+                (to > idx + 1).then_some((to, Token::Issue))
+            }
+            b'@' => {
+                let mut to = run(idx + 1, |b| b.is_ascii_alphanumeric() || b == b'-');
+                while to > idx + 1 && bytes[to - 1] == b'-' {
+                    to -= 1;
+                }
+                let team = to < end && bytes[to] == b'/';
+                (to > idx + 1 && !team).then_some((to, Token::Mention))
+            }
+            b'0'..=b'9' | b'a'..=b'f' => {
+                let to = run(idx, |b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+                let sha = &bytes[idx..to];
+                ((7..=40).contains(&sha.len())
+                    && sha.iter().any(u8::is_ascii_digit)
+                    && sha.iter().any(u8::is_ascii_alphabetic))
+                .then_some((to, Token::Commit))
+            }
+            _ => None,
+        };
+
+        match token {
+            Some((to, token)) if to >= end || !is_word(bytes[to]) => {
+                found.push((idx, to, token));
+                idx = to;
+            }
+            _ => idx += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REPO: &str = "https://github.com/example/widget";
+
+    #[test]
+    fn links_tokens() {
+        assert_eq!(
+            autolink(
+                "Fixed #12 in abc1234, thanks @alice-b!",
+                REPO,
+                Forge::Github
+            ),
+            "Fixed [#12](https://github.com/example/widget/issues/12) in \
+             [abc1234](https://github.com/example/widget/commit/abc1234), \
+             thanks [@alice-b](https://github.com/alice-b)!"
+        );
+    }
+
+    #[test]
+    fn skips_code_links_and_urls() {
+        let markdown = "`#1` [#2](https://example.com) <https://example.com/#3> \
+                        https://github.com/example/widget/commit/abc1234 me@example.com \\#4 &#123; 2025-01-01\n\n```\n#5\n```\n";
+        assert_eq!(autolink(markdown, REPO, Forge::Github), markdown);
+    }
+
+    #[test]
+    fn detects_forges() {
+        assert_eq!(Forge::detect(REPO), Some(Forge::Github));
+        assert_eq!(
+            Forge::detect("https://gitlab.example.com/group/project"),
+            Some(Forge::Gitlab)
+        );
+        assert_eq!(
+            Forge::detect("https://codeberg.org/a/b"),
+            Some(Forge::Gitea)
+        );
+        assert_eq!(Forge::detect("https://example.com/a/b"), None);
+        assert_eq!(
+            autolink("#7", "https://gitlab.com/a/b", Forge::Gitlab),
+            "[#7](https://gitlab.com/a/b/-/issues/7)"
+        );
+    }
+}
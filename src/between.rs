@@ -0,0 +1,218 @@
+//! Aggregating the changes released between two versions, for upgrade notes
+
+use std::fmt::Write;
+
+use anyhow::bail;
+use serde_json::{Map, Value, json};
+
+use crate::{Category, Changelog, Entry, RenderOptions, Version, render_to_string};
+
+/// The releases after one version, up to and including another
+#[derive(Debug, Clone)]
+pub struct Between<'a> {
+    /// The version upgraded from, whose own changes are excluded
+    pub since: &'a str,
+    /// The version upgraded to, whose changes are included
+    pub until: &'a str,
+    /// Releases in the range, newest first
+    pub versions: Vec<&'a Version>,
+}
+
+impl Changelog {
+    /// The releases after `since`, up to and including `until`, including archived releases
+    pub fn between<'a>(&'a self, since: &'a str, until: &'a str) -> anyhow::Result<Between<'a>> {
+        if self.compare_versions(since, until).is_gt() {
+            bail!("{} is newer than {}", since, until);
+        }
+        let mut versions = self
+            .all_versions()
+            .filter(|v| {
+                self.compare_versions(&v.version, since).is_gt()
+                    && self.compare_versions(&v.version, until).is_le()
+            })
+            .collect::<Vec<_>>();
+        if versions.is_empty() {
+            bail!("No versions were released after {} up to {}", since, until);
+        }
+        versions.sort_by(|a, b| self.compare_versions(&b.version, &a.version));
+
+        Ok(Between {
+            since,
+            until,
+            versions,
+        })
+    }
+}
+
+impl<'a> Between<'a> {
+    /// Changes in `category`, newest first, with the version each was released in
+    pub fn changes(&self, category: Category) -> Vec<(&'a Version, &'a Entry)> {
+        self.versions
+            .iter()
+            .flat_map(|version| {
+                version
+                    .changes
+                    .get(category)
+                    .iter()
+                    .map(move |entry| (*version, entry))
+            })
+            .collect()
+    }
+
+    /// Yanked releases in the range
+    pub fn yanked(&self) -> impl Iterator<Item = &'a Version> {
+        self.versions
+            .clone()
+            .into_iter()
+            .filter(|v| v.yanked.is_some())
+    }
+
+    /// Render as one Markdown list per category, each entry annotated with its version
+    pub fn render(&self, options: &RenderOptions) -> String {
+        render_to_string(|f| self.write_markdown(f, options))
+    }
+
+    fn write_markdown(&self, f: &mut impl Write, options: &RenderOptions) -> std::fmt::Result {
+        writeln!(f, "# Changes from {} to {}", self.since, self.until)?;
+
+        let yanked = self.yanked().collect::<Vec<_>>();
+        if !yanked.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Yanked releases:")?;
+            writeln!(f)?;
+            for version in yanked {
+                let reason = version.yanked.as_deref().unwrap_or_default();
+                writeln!(f, "- {}: {}", version.version, reason.trim())?;
+            }
+        }
+
+        for category in &options.categories {
+            let changes = self.changes(*category);
+            if changes.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            writeln!(f, "## {}", category.title())?;
+            writeln!(f)?;
+            for (version, entry) in changes {
+                annotated(version, entry).write_item(f, 0)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Export as JSON, with the releases in the range and the changes in each category
+    pub fn to_json(&self, options: &RenderOptions) -> anyhow::Result<Value> {
+        let versions = self
+            .versions
+            .iter()
+            .map(|version| {
+                json!({
+                    "version": version.version,
+                    "date": version.date,
+                    "yanked": version.yanked,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut changes = Map::new();
+        for category in &options.categories {
+            let entries = self
+                .changes(*category)
+                .into_iter()
+                .map(|(version, entry)| {
+                    Ok(json!({
+                        "version": version.version,
+                        "yanked": version.yanked.is_some(),
+                        "text": entry.text,
+                        "breaking": entry.breaking,
+                        "advisory": serde_json::to_value(&entry.advisory)?,
+                        "children": serde_json::to_value(&entry.children)?,
+                    }))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            if !entries.is_empty() {
+                changes.insert(category.key().to_string(), entries.into());
+            }
+        }
+
+        Ok(json!({
+            "since": self.since,
+            "until": self.until,
+            "versions": versions,
+            "changes": changes,
+        }))
+    }
+}
+
+/// `entry` with its version noted at the end of its first line, e.g. `Fix (1.2.3, yanked)`
+fn annotated(version: &Version, entry: &Entry) -> Entry {
+    let mut notes = vec![version.version.as_str()];
+    if entry.breaking {
+        notes.push("breaking");
+    }
+    if version.yanked.is_some() {
+        notes.push("yanked");
+    }
+
+    let (first, rest) = entry
+        .text
+        .split_once('\n')
+        .map_or((entry.text.as_str(), None), |(first, rest)| {
+            (first, Some(rest))
+        });
+    let mut text = format!("{} ({})", first.trim_end(), notes.join(", "));
+    if let Some(rest) = rest {
+        text = format!("{}\n{}", text, rest);
+    }
+    Entry {
+        text,
+        ..entry.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aggregates_changes_in_range() {
+        let changelog = Changelog {
+            versions: vec![
+                Version::fixture("2.0.0", &["Too new"]),
+                Version::fixture("1.3.0", &["Newest fix"]),
+                Version {
+                    yanked: Some("Broke the build".to_string()),
+                    ..Version::fixture("1.2.1", &["Yanked fix\n\nMore details"])
+                },
+                Version::fixture("1.2.0", &["Too old"]),
+            ],
+            ..Default::default()
+        };
+        let between = changelog.between("1.2.0", "1.3.0").unwrap();
+        assert_eq!(
+            between.render(&RenderOptions::default()),
+            "# Changes from 1.2.0 to 1.3.0\n\
+             \n\
+             Yanked releases:\n\
+             \n\
+             - 1.2.1: Broke the build\n\
+             \n\
+             ## Fixed\n\
+             \n\
+             - Newest fix (1.3.0)\n\
+             - Yanked fix (1.2.1, yanked)\n\
+             \n\
+             \x20 More details\n"
+        );
+
+        let json = between.to_json(&RenderOptions::default()).unwrap();
+        assert_eq!(json["versions"].as_array().unwrap().len(), 2);
+        assert_eq!(json["changes"]["fixed"][1]["version"], "1.2.1");
+        assert_eq!(json["changes"]["fixed"][1]["yanked"], true);
+
+        assert!(changelog.between("1.3.0", "1.2.0").is_err());
+        assert!(changelog.between("1.3.0", "1.4.0").is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Project configuration for changelog-md
+//!
+//! Configuration is read from a `.changelog-md.toml` file, or from the
+//! `[package.metadata.changelog-md]` table of a `Cargo.toml`, found by
+//! searching from the current directory up to the repository root.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::split::SplitOptions;
+use crate::{Category, RenderOptions};
+
+/// Name of the dedicated configuration file
+pub const CONFIG_FILE: &str = ".changelog-md.toml";
+
+/// Project defaults for changelog-md
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Path to the changelog source, relative to the configuration file
+    pub changelog: Option<PathBuf>,
+    /// Path to render Markdown to, relative to the configuration file
+    pub output: Option<PathBuf>,
+    /// Template used to generate tags for new releases, e.g. `v{version}`
+    ///
+    /// A changelog's own `tag_template` takes precedence
+    pub tag_template: Option<String>,
+    /// Git remote used to detect the repository url, defaults to `origin`
+    pub remote: Option<String>,
+    /// Categories that may be used, in the order they are rendered
+    pub categories: Option<Vec<Category>>,
+    /// Markdown rendering options
+    pub render: RenderOptions,
+    /// Options for `render --split`, writing a page per release
+    pub split: SplitOptions,
+
+    /// Directory the configuration was loaded from
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct CargoManifest {
+    package: Option<CargoPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    metadata: Option<CargoMetadata>,
+}
+
+#[derive(Deserialize)]
+struct CargoMetadata {
+    #[serde(rename = "changelog-md")]
+    changelog_md: Option<Config>,
+}
+
+impl Config {
+    /// Search from `start` upwards for a configuration
+    ///
+    /// The search stops at the first directory containing `.git`.
+    /// A `.changelog-md.toml` takes precedence over `Cargo.toml` in the same directory.
+    pub fn discover(start: &Path) -> anyhow::Result<Option<Config>> {
+        for dir in start.ancestors() {
+            if let Some(config) = Self::from_dir(dir)? {
+                return Ok(Some(config));
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load the configuration stored directly in `dir`, if any
+    pub fn from_dir(dir: &Path) -> anyhow::Result<Option<Config>> {
+        let config_file = dir.join(CONFIG_FILE);
+        if config_file.is_file() {
+            let contents = std::fs::read_to_string(&config_file)?;
+            let mut config: Config = toml::from_str(&contents)
+                .with_context(|| format!("Invalid configuration {}", config_file.display()))?;
+            config.root = dir.to_path_buf();
+            return Ok(Some(config));
+        }
+
+        let manifest = dir.join("Cargo.toml");
+        if manifest.is_file() {
+            let contents = std::fs::read_to_string(&manifest)?;
+            let manifest: CargoManifest = toml::from_str(&contents)
+                .with_context(|| format!("Invalid configuration {}", manifest.display()))?;
+            if let Some(mut config) = manifest
+                .package
+                .and_then(|p| p.metadata)
+                .and_then(|m| m.changelog_md)
+            {
+                config.root = dir.to_path_buf();
+                return Ok(Some(config));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The configured changelog source path
+    pub fn changelog_path(&self) -> Option<PathBuf> {
+        self.changelog.as_ref().map(|p| self.root.join(p))
+    }
+
+    /// The configured Markdown output path
+    pub fn output_path(&self) -> Option<PathBuf> {
+        self.output.as_ref().map(|p| self.root.join(p))
+    }
+
+    /// Rendering options, with the configured categories applied
+    pub fn render_options(&self) -> RenderOptions {
+        let mut options = self.render.clone();
+        if let Some(categories) = &self.categories {
+            options.categories = categories.clone();
+        }
+        options
+    }
+}
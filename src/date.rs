@@ -0,0 +1,126 @@
+//! Calendar dates, as release dates are written in changelog sources
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+
+/// A calendar date, written as `YYYY-MM-DD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// A date from its parts, or `None` if the day doesn't exist
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year.is_multiple_of(4)
+                && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days)
+            .contains(&day)
+            .then_some(Date { year, month, day })
+    }
+
+    /// The year
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// The month, from 1 to 12
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// The day of the month, from 1
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// The day of the week, from 0 for Monday to 6 for Sunday
+    pub fn weekday(&self) -> u8 {
+        // Sakamoto's method, counting from Sunday
+        const OFFSETS: [u16; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let year = self.year - u16::from(self.month < 3);
+        let sunday_based = (year + year / 4 - year / 100
+            + year / 400
+            + OFFSETS[usize::from(self.month - 1)]
+            + u16::from(self.day))
+            % 7;
+        ((sunday_based + 6) % 7) as u8
+    }
+
+    /// The current local date, read from the system clock with the `clock` feature
+    #[cfg(feature = "clock")]
+    pub fn today() -> Date {
+        use chrono::Datelike;
+
+        let now = chrono::Local::now().date_naive();
+        Date::new(now.year() as u16, now.month() as u8, now.day() as u8)
+            .expect("chrono dates are valid")
+    }
+}
+
+impl FromStr for Date {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Date> {
+        let invalid = || anyhow!("invalid date `{}`, expected YYYY-MM-DD", s);
+        let parts = s.split('-').collect::<Vec<_>>();
+        let [year, month, day] = parts[..] else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        if !s.bytes().all(|b| b.is_ascii_digit() || b == b'-') {
+            return Err(invalid());
+        }
+
+        let (year, month, day) = (year.parse()?, month.parse()?, day.parse()?);
+        match Date::new(year, month, day) {
+            Some(date) => Ok(date),
+            None => bail!("invalid date `{}`, there is no such day", s),
+        }
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_dates() {
+        let date = "2024-02-29".parse::<Date>().unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2024, 2, 29));
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert!(date < "2024-03-01".parse().unwrap());
+        assert_eq!(date.weekday(), 3);
+        assert_eq!("2025-06-01".parse::<Date>().unwrap().weekday(), 6);
+
+        for invalid in [
+            "2023-02-29",
+            "2025-13-01",
+            "2025-1-01",
+            "25-01-01",
+            "2025-01-01T00:00",
+        ] {
+            assert!(invalid.parse::<Date>().is_err(), "{}", invalid);
+        }
+    }
+}
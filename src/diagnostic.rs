@@ -0,0 +1,406 @@
+//! Diagnostics pointing at the offending part of a changelog source
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::Format;
+use crate::validate::{Problem, Severity};
+
+/// A problem in a changelog source, located by line and column where possible
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Whether the problem prevents the changelog being used
+    pub severity: Severity,
+    /// What is wrong
+    pub message: String,
+    /// Path to the offending value, e.g. `versions.1.2.0.fixed[3]`
+    pub path: Option<String>,
+    /// The source file, `-` for stdin
+    pub file: Option<PathBuf>,
+    /// Where the problem is in the source
+    pub span: Option<Span>,
+}
+
+/// A location within a single line of a changelog source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    /// One-based line number
+    pub line: usize,
+    /// One-based column, counted in characters
+    pub column: usize,
+    /// One-based column after the end of the span, on the same line
+    pub end_column: usize,
+    /// The full text of the line, for annotated snippets
+    pub text: String,
+}
+
+impl Span {
+    /// Locate a byte range of `source`, clipped to the line it starts on
+    pub fn new(source: &str, range: Range<usize>) -> Span {
+        let start = floor_char_boundary(source, range.start.min(source.len()));
+        let line_start = source[..start].rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |idx| start + idx);
+        let end = floor_char_boundary(source, range.end.clamp(start, line_end));
+
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        let column = source[line_start..start].chars().count() + 1;
+        let width = source[start..end].chars().count().max(1);
+        Span {
+            line: source[..start].matches('\n').count() + 1,
+            column,
+            end_column: column + width,
+            text: text.to_string(),
+        }
+    }
+
+    /// Locate a one-based line and column of `source`
+    pub fn at(source: &str, line: usize, column: usize) -> Span {
+        let line_start = source
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>();
+        let offset = source[line_start..]
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(source.len(), |(idx, _)| line_start + idx);
+        Span::new(source, offset..offset + 1)
+    }
+}
+
+fn floor_char_boundary(s: &str, mut idx: usize) -> usize {
+    while !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+impl Diagnostic {
+    /// A diagnostic without a location
+    pub fn new(severity: Severity, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            path: None,
+            file: None,
+            span: None,
+        }
+    }
+
+    /// Describe an error from parsing `source` as `format`, locating it in the source
+    pub fn from_error(err: &anyhow::Error, source: &str, format: Format) -> Diagnostic {
+        if let Some(diagnostic) = err.downcast_ref::<Diagnostic>() {
+            return diagnostic.clone();
+        }
+
+        let (path, message, span) = parse_error(err, source);
+        let path = path.filter(|path| !path.is_empty() && path != ".");
+        let span = span.or_else(|| {
+            let range = locate(source, format, path.as_deref()?)?;
+            Some(Span::new(source, range))
+        });
+        Diagnostic {
+            severity: Severity::Error,
+            message: strip_location(&message).to_string(),
+            path,
+            file: None,
+            span,
+        }
+    }
+
+    /// Describe a validation problem, locating its path in `source`
+    pub fn from_problem(problem: &Problem, source: &str, format: Format) -> Diagnostic {
+        Diagnostic {
+            severity: problem.severity,
+            message: problem.message.clone(),
+            path: Some(problem.path.clone()),
+            file: None,
+            span: locate(source, format, &problem.path).map(|range| Span::new(source, range)),
+        }
+    }
+
+    /// Set the source file this diagnostic refers to
+    pub fn with_file(mut self, file: impl Into<PathBuf>) -> Diagnostic {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Set the file of a diagnostic wrapped in an error, leaving other errors untouched
+    pub fn attach_file(err: anyhow::Error, file: &Path) -> anyhow::Error {
+        match err.downcast::<Diagnostic>() {
+            Ok(diagnostic) => diagnostic.with_file(file).into(),
+            Err(err) => err,
+        }
+    }
+
+    fn file_name(&self) -> Option<String> {
+        self.file.as_ref().map(|file| match file == Path::new("-") {
+            true => "<stdin>".to_string(),
+            false => file.display().to_string(),
+        })
+    }
+
+    /// A machine readable form, for editors and CI annotations
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "severity": self.severity.name(),
+            "message": self.message,
+            "path": self.path,
+            "file": self.file_name(),
+        });
+        if let Some(span) = &self.span {
+            json["line"] = span.line.into();
+            json["column"] = span.column.into();
+            json["end_column"] = span.end_column.into();
+        }
+        json["rendered"] = self.to_string().into();
+        json
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.severity.name())?;
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path)?;
+        }
+        write!(f, "{}", self.message)?;
+
+        let file = self.file_name();
+        match (&self.span, file) {
+            (Some(span), file) => {
+                let gutter = " ".repeat(span.line.to_string().len());
+                write!(f, "\n{}--> ", gutter)?;
+                if let Some(file) = file {
+                    write!(f, "{}:", file)?;
+                }
+                write!(f, "{}:{}", span.line, span.column)?;
+                write!(f, "\n{} |", gutter)?;
+                write!(f, "\n{} | {}", span.line, span.text)?;
+
+                let indent = span
+                    .text
+                    .chars()
+                    .take(span.column - 1)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect::<String>();
+                let carets = "^".repeat(span.end_column - span.column);
+                write!(f, "\n{} | {}{}", gutter, indent, carets)
+            }
+            (None, Some(file)) => write!(f, "\n --> {}", file),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// The path, message and location of an error from one of the format parsers
+fn parse_error(err: &anyhow::Error, source: &str) -> (Option<String>, String, Option<Span>) {
+    #[cfg(feature = "yaml")]
+    {
+        let located = |err: &serde_yml::Error| {
+            err.location()
+                .map(|location| Span::new(source, location.index()..location.index()))
+        };
+        if let Some(err) = err.downcast_ref::<serde_path_to_error::Error<serde_yml::Error>>() {
+            return (
+                Some(err.path().to_string()),
+                err.inner().to_string(),
+                located(err.inner()),
+            );
+        }
+        if let Some(err) = err.downcast_ref::<serde_yml::Error>() {
+            return (None, err.to_string(), located(err));
+        }
+    }
+    #[cfg(feature = "toml")]
+    {
+        let located = |err: &toml::de::Error| err.span().map(|span| Span::new(source, span));
+        if let Some(err) = err.downcast_ref::<serde_path_to_error::Error<toml::de::Error>>() {
+            let message = err.inner().message().to_string();
+            return (Some(err.path().to_string()), message, located(err.inner()));
+        }
+        if let Some(err) = err.downcast_ref::<toml::de::Error>() {
+            return (None, err.message().to_string(), located(err));
+        }
+    }
+    #[cfg(feature = "json5")]
+    {
+        let located = |location: &Option<json5::Location>| {
+            location
+                .as_ref()
+                .map(|location| Span::at(source, location.line, location.column))
+        };
+        if let Some(err) = err.downcast_ref::<serde_path_to_error::Error<json5::Error>>() {
+            let json5::Error::Message { msg, location } = err.inner();
+            return (Some(err.path().to_string()), msg.clone(), located(location));
+        }
+        if let Some(json5::Error::Message { msg, location }) = err.downcast_ref::<json5::Error>() {
+            return (None, msg.clone(), located(location));
+        }
+    }
+    #[cfg(feature = "ron")]
+    if let Some(err) = err.downcast_ref::<ron::error::SpannedError>() {
+        let span = Span::at(source, err.position.line, err.position.col);
+        return (None, err.code.to_string(), Some(span));
+    }
+
+    // JSON, and every format read through a JSON value
+    let located = |err: &serde_json::Error| {
+        (err.line() > 0).then(|| Span::at(source, err.line(), err.column()))
+    };
+    if let Some(err) = err.downcast_ref::<serde_path_to_error::Error<serde_json::Error>>() {
+        return (
+            Some(err.path().to_string()),
+            err.inner().to_string(),
+            located(err.inner()),
+        );
+    }
+    if let Some(err) = err.downcast_ref::<serde_json::Error>() {
+        return (None, err.to_string(), located(err));
+    }
+    (None, format!("{:#}", err), None)
+}
+
+/// Remove a trailing `at line N column M`, which is shown in the snippet instead
+fn strip_location(message: &str) -> &str {
+    if let Some((head, tail)) = message.rsplit_once(" at line ")
+        && let Some((line, column)) = tail.split_once(" column ")
+        && line.parse::<usize>().is_ok()
+        && column.parse::<usize>().is_ok()
+    {
+        return head;
+    }
+    message
+}
+
+/// Best-effort search for the source text of the value at `path`
+///
+/// Keys are matched against the parsed document, so keys containing dots such as
+/// versions resolve correctly, then searched for in order through the source.
+/// List items are found by the first line of their text.
+fn locate(source: &str, format: Format, path: &str) -> Option<Range<usize>> {
+    let document = format.parse_value(source).ok()?;
+    let mut value = &document;
+    let mut rest = path;
+    let mut found: Option<Range<usize>> = None;
+
+    while !rest.is_empty() {
+        let from = found.as_ref().map_or(0, |range| range.end);
+        if let Some(index) = rest.strip_prefix('[') {
+            let (index, tail) = index.split_once(']')?;
+            value = value.get(index.parse::<usize>().ok()?)?;
+            rest = tail.trim_start_matches('.');
+
+            let text = match value {
+                serde_json::Value::String(text) => Some(text.as_str()),
+                serde_json::Value::Object(map) => map.get("text").and_then(|text| text.as_str()),
+                _ => None,
+            };
+            // Stop before anything a format might escape or fold
+            let needle = text
+                .and_then(|text| text.lines().map(str::trim).find(|line| !line.is_empty()))
+                .map(|line| {
+                    let end = line
+                        .char_indices()
+                        .find(|(_, c)| matches!(c, '"' | '\'' | '\\'))
+                        .map_or(line.len(), |(idx, _)| idx);
+                    &line[..floor_char_boundary(line, end.min(60))]
+                })
+                .filter(|needle| !needle.is_empty());
+            if let Some(needle) = needle
+                && let Some(idx) = source[from..].find(needle)
+            {
+                found = Some(from + idx..from + idx + needle.len());
+            }
+        } else {
+            let map = value.as_object()?;
+            let key = map
+                .keys()
+                .filter(|key| {
+                    rest.strip_prefix(key.as_str())
+                        .is_some_and(|tail| tail.is_empty() || tail.starts_with(['.', '[']))
+                })
+                .max_by_key(|key| key.len())?;
+            value = &map[key];
+            rest = rest[key.len()..].trim_start_matches('.');
+            if let Some(idx) = find_key(&source[from..], key) {
+                found = Some(from + idx..from + idx + key.len());
+            }
+        }
+    }
+
+    found
+}
+
+/// Find `key` as a whole token, not as part of a longer word
+fn find_key(source: &str, key: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '$');
+    source.match_indices(key).map(|(idx, _)| idx).find(|&idx| {
+        !source[..idx].chars().next_back().is_some_and(is_word)
+            && !source[idx + key.len()..]
+                .chars()
+                .next()
+                .is_some_and(is_word)
+    })
+}
+
+#[cfg(all(test, feature = "yaml", feature = "toml"))]
+mod test {
+    use super::*;
+
+    const YAML: &str = "title: Changelog
+description: ''
+repository: ''
+unreleased: {}
+versions:
+  1.0.0:
+    tag: v1.0.0
+    date: [2025]
+    fixed:
+      - First
+      - Second `fix
+";
+
+    #[test]
+    fn locates_semantic_errors() {
+        let problem = Problem {
+            severity: Severity::Error,
+            path: "versions.1.0.0.fixed[1]".to_string(),
+            message: "unbalanced backtick".to_string(),
+        };
+        let diagnostic =
+            Diagnostic::from_problem(&problem, YAML, Format::Yaml).with_file("CHANGELOG.yml");
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: versions.1.0.0.fixed[1]: unbalanced backtick
+  --> CHANGELOG.yml:11:9
+   |
+11 |       - Second `fix
+   |         ^^^^^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn locates_parse_errors() {
+        let err = Format::Yaml.parse(YAML).unwrap_err();
+        let diagnostic = err.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.path.as_deref(), Some("versions.1.0.0.date"));
+        assert_eq!(diagnostic.span.as_ref().map(|span| span.line), Some(8));
+
+        let toml = "title = \"Changelog\"\n[versions.\"1.0.0\"]\ntag = 1\n";
+        let err = Format::Toml.parse(toml).unwrap_err();
+        let diagnostic = err.downcast_ref::<Diagnostic>().unwrap();
+        let span = diagnostic.span.as_ref().unwrap();
+        assert_eq!((span.line, span.column), (3, 7));
+        assert_eq!(diagnostic.to_json()["line"], 3);
+
+        let err = Format::Json.parse("{\n  \"title\": }").unwrap_err();
+        let diagnostic = err.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.span.as_ref().map(|span| span.line), Some(2));
+        assert!(!diagnostic.message.contains("at line"));
+    }
+}
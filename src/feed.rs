@@ -0,0 +1,171 @@
+//! Atom and RSS feeds of released versions
+
+use anyhow::Context;
+
+use crate::{Changelog, Date, RenderOptions, render_to_string};
+
+/// Options controlling feed generation
+#[derive(Debug, Default, Clone)]
+pub struct FeedOptions {
+    /// Maximum number of versions to include, newest first
+    pub max_entries: Option<usize>,
+    /// Options used to render each version's changes
+    pub render: RenderOptions,
+}
+
+/// A version prepared for use as a feed entry
+struct Entry {
+    date: Date,
+    link: String,
+    title: String,
+    content: String,
+}
+
+impl Changelog {
+    /// Render released versions as an Atom feed
+    pub fn to_atom(&self, options: &FeedOptions) -> anyhow::Result<String> {
+        let entries = self.feed_entries(options)?;
+        let repository = self.repository.trim_end_matches('/');
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        out += &format!("  <id>{}/releases</id>\n", escape(repository));
+        out += &format!("  <title>{}</title>\n", escape(&self.title));
+        // Left out until there are releases, so the feed only changes when they do
+        if let Some(updated) = self.last_release_date() {
+            out += &format!("  <updated>{}</updated>\n", rfc3339(updated));
+        }
+        out += &format!("  <link href=\"{}\"/>\n", escape(repository));
+        out += &format!("  <author><name>{}</name></author>\n", escape(&self.title));
+        for entry in entries {
+            out.push_str("  <entry>\n");
+            out += &format!("    <id>{}</id>\n", escape(&entry.link));
+            out += &format!("    <title>{}</title>\n", escape(&entry.title));
+            out += &format!("    <updated>{}</updated>\n", rfc3339(entry.date));
+            out += &format!("    <link href=\"{}\"/>\n", escape(&entry.link));
+            out += &format!(
+                "    <content type=\"html\">{}</content>\n",
+                escape(&entry.content)
+            );
+            out.push_str("  </entry>\n");
+        }
+        out.push_str("</feed>\n");
+
+        Ok(out)
+    }
+
+    /// Render released versions as an RSS 2.0 feed
+    pub fn to_rss(&self, options: &FeedOptions) -> anyhow::Result<String> {
+        let entries = self.feed_entries(options)?;
+        let repository = self.repository.trim_end_matches('/');
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<rss version=\"2.0\">\n");
+        out.push_str("  <channel>\n");
+        out += &format!("    <title>{}</title>\n", escape(&self.title));
+        out += &format!("    <link>{}</link>\n", escape(repository));
+        out += &format!(
+            "    <description>{}</description>\n",
+            escape(self.description.trim())
+        );
+        if let Some(updated) = self.last_release_date() {
+            out += &format!("    <lastBuildDate>{}</lastBuildDate>\n", rfc2822(updated));
+        }
+        for entry in entries {
+            out.push_str("    <item>\n");
+            out += &format!("      <title>{}</title>\n", escape(&entry.title));
+            out += &format!("      <link>{}</link>\n", escape(&entry.link));
+            out += &format!(
+                "      <guid isPermaLink=\"true\">{}</guid>\n",
+                escape(&entry.link)
+            );
+            out += &format!("      <pubDate>{}</pubDate>\n", rfc2822(entry.date));
+            out += &format!(
+                "      <description>{}</description>\n",
+                escape(&entry.content)
+            );
+            out.push_str("    </item>\n");
+        }
+        out.push_str("  </channel>\n");
+        out.push_str("</rss>\n");
+
+        Ok(out)
+    }
+
+    /// The most recent release date, including releases left out of the feed
+    fn last_release_date(&self) -> Option<Date> {
+        self.all_versions()
+            .filter_map(|version| version.release_date().ok())
+            .max()
+    }
+
+    fn feed_entries(&self, options: &FeedOptions) -> anyhow::Result<Vec<Entry>> {
+        let repository = self.repository.trim_end_matches('/');
+        self.all_versions()
+            .take(options.max_entries.unwrap_or(usize::MAX))
+            .map(|version| {
+                let date = version
+                    .release_date()
+                    .with_context(|| format!("Invalid date for version {}", version.version))?;
+
+                let mut title = version.version.clone();
+                let mut markdown = String::new();
+                if let Some(reason) = &version.yanked {
+                    title += " [YANKED]";
+                    markdown += &format!("**Yanked:** {}\n\n", reason);
+                }
+                markdown += &render_to_string(|f| version.write_body(f, &options.render));
+
+                Ok(Entry {
+                    date,
+                    link: format!("{}/releases/tag/{}", repository, version.tag),
+                    title,
+                    content: markdown_to_html(&self.autolink(markdown, &options.render)),
+                })
+            })
+            .collect()
+    }
+}
+
+fn markdown_to_html(markdown: &str) -> String {
+    let parser = pulldown_cmark::Parser::new(markdown);
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    html
+}
+
+fn rfc3339(date: Date) -> String {
+    format!("{}T00:00:00Z", date)
+}
+
+fn rfc2822(date: Date) -> String {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    format!(
+        "{}, {:02} {} {:04} 00:00:00 +0000",
+        WEEKDAYS[usize::from(date.weekday())],
+        date.day(),
+        MONTHS[usize::from(date.month() - 1)],
+        date.year()
+    )
+}
+
+/// Escape text for use in XML content and attributes
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
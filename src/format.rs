@@ -0,0 +1,198 @@
+//! Source formats a Changelog can be read from and written to
+
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use crate::Changelog;
+use crate::diagnostic::Diagnostic;
+
+/// A changelog source format
+///
+/// Every format can be named, but only those enabled by cargo features can be read and written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Format {
+    /// YAML
+    #[cfg_attr(feature = "cli", value(alias("yml")))]
+    Yaml,
+    /// TOML
+    Toml,
+    /// JSON
+    Json,
+    /// JSON5, JSON with comments and trailing commas
+    Json5,
+    /// Rusty Object Notation
+    Ron,
+    /// KDL Document Language
+    Kdl,
+}
+
+impl Default for Format {
+    /// YAML, or JSON when YAML support is not enabled
+    fn default() -> Self {
+        match cfg!(feature = "yaml") {
+            true => Format::Yaml,
+            false => Format::Json,
+        }
+    }
+}
+
+impl Format {
+    /// All supported formats
+    pub const ALL: [Format; 6] = [
+        Format::Yaml,
+        Format::Toml,
+        Format::Json,
+        Format::Json5,
+        Format::Ron,
+        Format::Kdl,
+    ];
+
+    /// The preferred file extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Yaml => "yml",
+            Format::Toml => "toml",
+            Format::Json => "json",
+            Format::Json5 => "json5",
+            Format::Ron => "ron",
+            Format::Kdl => "kdl",
+        }
+    }
+
+    /// The cargo feature enabling this format, if it is optional
+    fn feature(&self) -> Option<&'static str> {
+        match self {
+            Format::Yaml => Some("yaml"),
+            Format::Toml => Some("toml"),
+            Format::Json5 => Some("json5"),
+            Format::Ron => Some("ron"),
+            Format::Kdl => Some("kdl"),
+            Format::Json => None,
+        }
+    }
+
+    /// Whether this format can be read and written with the enabled cargo features
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Format::Yaml => cfg!(feature = "yaml"),
+            Format::Toml => cfg!(feature = "toml"),
+            Format::Json5 => cfg!(feature = "json5"),
+            Format::Ron => cfg!(feature = "ron"),
+            Format::Kdl => cfg!(feature = "kdl"),
+            Format::Json => true,
+        }
+    }
+
+    /// The error for using a format whose feature is not enabled
+    fn disabled(&self) -> anyhow::Error {
+        anyhow!(
+            "{:?} support is not enabled, build changelog-md with the `{}` feature",
+            self,
+            self.feature().unwrap_or_default()
+        )
+    }
+
+    /// Determine the format from a path's extension
+    pub fn from_extension(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_ascii_lowercase();
+        match ext.to_str()? {
+            "yml" | "yaml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            "json" => Some(Format::Json),
+            "json5" => Some(Format::Json5),
+            "ron" => Some(Format::Ron),
+            "kdl" => Some(Format::Kdl),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of a changelog source from its contents
+    ///
+    /// Only the syntax is checked, so that parsing in the detected format reports any errors
+    pub fn detect(s: &str) -> Option<Format> {
+        // Stricter formats first, as JSON is also valid JSON5 and YAML.
+        // YAML reads KDL as a plain string, which isn't a map, but KDL reads YAML as nodes
+        [
+            Format::Json,
+            Format::Json5,
+            Format::Toml,
+            Format::Ron,
+            Format::Yaml,
+            Format::Kdl,
+        ]
+        .into_iter()
+        .find(|format| format.parse_value(s).is_ok_and(|value| value.is_object()))
+    }
+
+    /// Determine the format of a source file from its extension,
+    /// falling back to its contents if the extension is unknown
+    pub fn from_path(path: &Path) -> anyhow::Result<Format> {
+        if let Some(format) = Self::from_extension(path) {
+            return Ok(format);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Self::detect(&contents).ok_or(anyhow!("Unable to detect the format of {}", path.display()))
+    }
+
+    /// Parse a Changelog in this format
+    ///
+    /// Errors are [`Diagnostic`]s, located in the source where possible
+    pub fn parse(&self, s: &str) -> anyhow::Result<Changelog> {
+        match self {
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Changelog::from_yaml(s),
+            #[cfg(feature = "toml")]
+            Format::Toml => Changelog::from_toml(s),
+            Format::Json => Changelog::from_json(s),
+            #[cfg(feature = "json5")]
+            Format::Json5 => Changelog::from_json5(s),
+            #[cfg(feature = "ron")]
+            Format::Ron => Changelog::from_ron(s),
+            #[cfg(feature = "kdl")]
+            Format::Kdl => Changelog::from_kdl(s),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.disabled()),
+        }
+        .map_err(|err| Diagnostic::from_error(&err, s, *self).into())
+    }
+
+    /// Parse a source without validating it against the current format
+    pub fn parse_value(&self, s: &str) -> anyhow::Result<serde_json::Value> {
+        match self {
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Ok(serde_yml::from_str(s)?),
+            #[cfg(feature = "toml")]
+            Format::Toml => Ok(toml::from_str(s)?),
+            Format::Json => Ok(serde_json::from_str(s)?),
+            #[cfg(feature = "json5")]
+            Format::Json5 => Ok(json5::from_str(s)?),
+            #[cfg(feature = "ron")]
+            Format::Ron => Ok(ron::from_str(s)?),
+            #[cfg(feature = "kdl")]
+            Format::Kdl => crate::kdl::from_str(s),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.disabled()),
+        }
+    }
+
+    /// Serialize a Changelog in this format
+    pub fn to_string(&self, changelog: &Changelog) -> anyhow::Result<String> {
+        match self {
+            #[cfg(feature = "yaml")]
+            Format::Yaml => changelog.to_yaml(),
+            #[cfg(feature = "toml")]
+            Format::Toml => changelog.to_toml(),
+            Format::Json => changelog.to_json(),
+            #[cfg(feature = "json5")]
+            Format::Json5 => changelog.to_json5(),
+            #[cfg(feature = "ron")]
+            Format::Ron => changelog.to_ron(),
+            #[cfg(feature = "kdl")]
+            Format::Kdl => changelog.to_kdl(),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.disabled()),
+        }
+    }
+}
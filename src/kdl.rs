@@ -0,0 +1,256 @@
+//! Mapping changelogs to and from KDL documents, using the `kdl` crate
+//!
+//! Changelogs are mapped to KDL documents as follows:
+//! - maps become nodes with children, keyed by node name
+//! - lists become nodes with `-` children, one per item
+//! - scalars become nodes with a single argument
+//!
+//! ```kdl
+//! title "My project"
+//! unreleased {
+//!     added {
+//!         - "A new feature"
+//!     }
+//! }
+//! ```
+//!
+//! Documents are read and written as KDL v2. KDL v1 documents are rejected, pointing out
+//! the v1 syntax to update.
+
+use anyhow::bail;
+use kdl::{KdlDocument, KdlEntry, KdlEntryFormat, KdlError, KdlNode, KdlValue};
+use serde_json::{Map, Number, Value};
+
+use crate::diagnostic::{Diagnostic, Span};
+use crate::validate::Severity;
+
+/// Serialize a map into a KDL document
+pub(crate) fn to_string(value: &Value) -> anyhow::Result<String> {
+    let Value::Object(map) = value else {
+        bail!("A KDL document must be a map");
+    };
+    let mut document = KdlDocument::new();
+    for (key, value) in map {
+        document.nodes_mut().push(to_node(key, value));
+    }
+    document.autoformat();
+    Ok(document.to_string())
+}
+
+/// Parse a KDL document into a map
+pub(crate) fn from_str(s: &str) -> anyhow::Result<Value> {
+    let document = KdlDocument::parse(s).map_err(|err| error(s, err))?;
+    nodes_to_value(document.nodes())
+}
+
+fn to_node(name: &str, value: &Value) -> KdlNode {
+    let mut node = KdlNode::new(name);
+    match value {
+        Value::Array(items) => {
+            let children = node.ensure_children();
+            for item in items {
+                children.nodes_mut().push(to_node("-", item));
+            }
+        }
+        Value::Object(map) => {
+            let children = node.ensure_children();
+            for (key, value) in map {
+                children.nodes_mut().push(to_node(key, value));
+            }
+        }
+        scalar => node.push(to_entry(scalar)),
+    }
+    node
+}
+
+fn to_entry(value: &Value) -> KdlEntry {
+    let mut entry = KdlEntry::new(match value {
+        Value::Bool(b) => KdlValue::Bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => KdlValue::Integer(n.into()),
+            None => match n.as_u64() {
+                Some(n) => KdlValue::Integer(n.into()),
+                None => KdlValue::Float(n.as_f64().unwrap_or_default()),
+            },
+        },
+        Value::String(s) => KdlValue::String(s.clone()),
+        Value::Null | Value::Array(_) | Value::Object(_) => KdlValue::Null,
+    });
+    // Multi-line text such as descriptions stays readable as a raw multi-line string,
+    // unless it has whitespace-only lines, which those strings don't keep
+    if let Value::String(s) = value
+        && s.contains('\n')
+        && !s.contains('\r')
+        && !s
+            .lines()
+            .any(|line| !line.is_empty() && line.trim().is_empty())
+    {
+        let mut hashes = "#".to_string();
+        while s.contains(&format!("\"\"\"{}", hashes)) {
+            hashes.push('#');
+        }
+        entry.set_format(KdlEntryFormat {
+            value_repr: format!("{hashes}\"\"\"\n{s}\n\"\"\"{hashes}"),
+            leading: " ".to_string(),
+            autoformat_keep: true,
+            ..Default::default()
+        });
+    }
+    entry
+}
+
+fn nodes_to_value(nodes: &[KdlNode]) -> anyhow::Result<Value> {
+    if !nodes.is_empty() && nodes.iter().all(|n| n.name().value() == "-") {
+        return nodes
+            .iter()
+            .map(node_to_value)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(Value::Array);
+    }
+
+    let mut map = Map::new();
+    for node in nodes {
+        let name = node.name().value();
+        if map.insert(name.to_string(), node_to_value(node)?).is_some() {
+            bail!("duplicate node `{}`", name);
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+fn node_to_value(node: &KdlNode) -> anyhow::Result<Value> {
+    if node.entries().iter().any(|entry| entry.name().is_some()) {
+        bail!(
+            "node `{}` has properties, which are not supported",
+            node.name()
+        );
+    }
+    let mut args = node.entries().iter().map(|entry| scalar(entry.value()));
+    match (node.entries().len(), node.children()) {
+        (0, children) => nodes_to_value(children.map_or(&[], |c| c.nodes())),
+        (1, None) => args.next().unwrap_or(Ok(Value::Null)),
+        (_, None) => args.collect::<anyhow::Result<Vec<_>>>().map(Value::Array),
+        (_, Some(_)) => bail!(
+            "node `{}` cannot have both arguments and children",
+            node.name()
+        ),
+    }
+}
+
+fn scalar(value: &KdlValue) -> anyhow::Result<Value> {
+    Ok(match value {
+        KdlValue::String(s) => Value::String(s.clone()),
+        KdlValue::Integer(n) => match (i64::try_from(*n), u64::try_from(*n)) {
+            (Ok(n), _) => n.into(),
+            (_, Ok(n)) => n.into(),
+            _ => bail!("{} is too large", n),
+        },
+        KdlValue::Float(n) => Number::from_f64(*n).map_or(Value::Null, Value::Number),
+        KdlValue::Bool(b) => Value::Bool(*b),
+        KdlValue::Null => Value::Null,
+    })
+}
+
+/// The first problem reported by the KDL parser, located in the source
+fn error(s: &str, err: KdlError) -> anyhow::Error {
+    let Some(diagnostic) = err.diagnostics.into_iter().next() else {
+        return Diagnostic::new(Severity::Error, "invalid KDL document").into();
+    };
+    let start = diagnostic.span.offset();
+    let range = start..start + diagnostic.span.len();
+    let message = match (
+        s.get(range.clone()).and_then(v1_syntax),
+        diagnostic.message,
+        diagnostic.help,
+    ) {
+        (Some(update), _, _) => format!("KDL v1 syntax is not supported, {}", update),
+        (None, Some(message), Some(help)) => format!("{}. {}", message, help),
+        (None, Some(message), None) => message,
+        (None, None, _) => "invalid KDL document".to_string(),
+    };
+    Diagnostic {
+        span: Some(Span::new(s, range)),
+        ..Diagnostic::new(Severity::Error, message)
+    }
+    .into()
+}
+
+/// How to update a value written in KDL v1 syntax, if it is one
+fn v1_syntax(value: &str) -> Option<String> {
+    if matches!(value, "true" | "false" | "null") {
+        Some(format!("write #{} in KDL v2", value))
+    } else if value.starts_with("r\"") || value.starts_with("r#") {
+        Some("write raw strings as #\"...\"# in KDL v2".to_string())
+    } else if value.starts_with('"') && value.contains("\\/") {
+        Some("write \\/ as / in KDL v2".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = serde_json::json!({
+            "$schema": "https://example.com",
+            "title": "A \"quoted\" title",
+            "description": "Multiple\nlines \"\"\"#\n",
+            "unreleased": {},
+            "versions": {
+                "1.0.0": {
+                    "tag": "v1.0.0",
+                    "added": ["First", "Second"],
+                },
+            },
+        });
+
+        let kdl = to_string(&value).unwrap();
+        assert_eq!(from_str(&kdl).unwrap(), value);
+    }
+
+    #[test]
+    fn comments_and_alternate_syntax() {
+        let kdl = r#"
+            // A line comment
+            title "Changelog" /* inline */
+            /-ignored "node"
+            list "a" "b" /-"c"; other (typed)#true
+            numbers {
+                - 0x10
+                - 1_000
+                - -2.5
+            }
+        "#;
+
+        assert_eq!(
+            from_str(kdl).unwrap(),
+            serde_json::json!({
+                "title": "Changelog",
+                "list": ["a", "b"],
+                "other": true,
+                "numbers": [16, 1000, -2.5],
+            })
+        );
+
+        // KDL v1 documents are rejected, pointing out what to update
+        for (v1, update) in [
+            ("yanked null", "write #null in KDL v2"),
+            (
+                "title r#\"Changelog\"#",
+                "write raw strings as #\"...\"# in KDL v2",
+            ),
+            ("title \"a\\/b\"", "write \\/ as / in KDL v2"),
+        ] {
+            let err = from_str(v1).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<Diagnostic>().unwrap().message,
+                format!("KDL v1 syntax is not supported, {}", update),
+                "{}",
+                v1
+            );
+        }
+    }
+}
@@ -3,17 +3,61 @@
 //! A serializable format for updating CHANGELOG files
 //! and generating CHANGELOG.md
 
-use anyhow::anyhow;
-use schemars::JsonSchema;
+pub mod advisory;
+#[cfg(feature = "markdown")]
+pub mod autolink;
+pub mod between;
+#[cfg(feature = "toml")]
+pub mod config;
+pub mod date;
+pub mod diagnostic;
+#[cfg(feature = "feed")]
+pub mod feed;
+pub mod format;
+#[cfg(feature = "kdl")]
+mod kdl;
+#[cfg(feature = "cli")]
+pub mod lsp;
+pub mod merge;
+pub mod migrate;
+pub mod split;
+pub mod validate;
+pub mod version;
+
+pub use date::Date;
+pub use format::Format;
+pub use migrate::SCHEMA_VERSION;
+pub use version::{Bump, Scheme, VersionNumber};
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use anyhow::{Context, anyhow};
 use serde::{Deserialize, Serialize};
 use serde_with::{KeyValueMap, serde_as};
 
+use advisory::Advisory;
+#[cfg(feature = "markdown")]
+use autolink::Forge;
+
 /// A user-friendly format for writing Changelogs in a
 /// verifiable and more git-friendly format
 #[serde_as]
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Changelog {
+    /// JSON Schema used by editors to validate this file
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Version of the changelog-md format this file is written in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Versioning scheme releases follow, detected from the latest release when unset
+    ///
+    /// Setting it makes `validate` reject versions that don't follow it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_scheme: Option<Scheme>,
     /// Your changelog's heading
     pub title: String,
     /// A description of your project.
@@ -21,15 +65,53 @@ pub struct Changelog {
     pub description: String,
     /// Your source repository link
     pub repository: String,
+    /// Name of the project this changelog covers, for repositories releasing several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    /// Template for release tags, e.g. `v{version}` or `{component}-v{version}`
+    ///
+    /// Takes precedence over the configured `tag-template`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_template: Option<String>,
+    /// Archive sources holding older releases, relative to this file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// The release unreleased changes are planned for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<Next>,
     /// Currently unreleased changes
-    pub unreleased: Changes,
+    pub unreleased: Unreleased,
     /// Releases
     #[serde_as(as = "KeyValueMap<_>")]
     pub versions: Vec<Version>,
+    /// Older releases loaded from the `include` sources, never written back to this source
+    #[serde(skip)]
+    pub archived: Vec<Version>,
+}
+
+/// A planned release, recording where unreleased changes are going
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Next {
+    /// The version unreleased changes will be released as, used by `release` by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Target release date as `YYYY-MM-DD`, or `YYYY-MM` for a month
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(regex(pattern = r"^\d{4}-[01]\d(-[0-3]\d)?$"))
+    )]
+    pub target: Option<String>,
+    /// Short Markdown summary of what the release is about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
 }
 
 /// A released version
-#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Version {
     /// The version name
@@ -38,7 +120,10 @@ pub struct Version {
     /// Git tag associated with this version
     pub tag: String,
     /// Date the version was released as an ISO Date String
-    #[schemars(regex(pattern = r"^\d{4}-[01]\d-[0-3]\d$"))]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(regex(pattern = r"^\d{4}-[01]\d-[0-3]\d$"))
+    )]
     pub date: String,
     /// Optional Markdown description of this version
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -46,62 +131,214 @@ pub struct Version {
     /// If a version was yanked, the reason why
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub yanked: Option<String>,
+    /// The version this release follows, if it isn't the next entry in `versions`
+    ///
+    /// Used for releases made from maintenance branches, e.g. `1.4.3` after `2.0.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+    /// Optional Markdown guide to migrating from earlier versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migration: Option<String>,
     /// Changes within this version
     #[serde(flatten)]
     pub changes: Changes,
 }
 
 /// Any changes made in this version
-#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Changes {
     /// New additions made in this version
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub added: Vec<String>,
+    pub added: Vec<Entry>,
     /// Changes to existing features
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub changed: Vec<String>,
+    pub changed: Vec<Entry>,
     /// Deprecations
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub deprecated: Vec<String>,
+    pub deprecated: Vec<Entry>,
     /// Changes the removed a feature
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub removed: Vec<String>,
+    pub removed: Vec<Entry>,
     /// Fixes to existing features
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub fixed: Vec<String>,
+    pub fixed: Vec<Entry>,
     /// Security changes
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub security: Vec<String>,
+    pub security: Vec<Entry>,
+}
+
+/// Unreleased changes, with changes held back for later releases in named tracks
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "UnreleasedSource", into = "UnreleasedSource")]
+pub struct Unreleased {
+    /// Changes for the next release
+    pub changes: Changes,
+    /// Changes for later releases by track name, e.g. `next-major`
+    pub tracks: BTreeMap<String, Changes>,
+}
+
+/// Unreleased changes as written in sources, with tracks under their own key
+///
+/// The categories are listed rather than flattened, so that unknown keys and
+/// invalid entries are reported at their path.
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+struct UnreleasedSource {
+    /// New additions made in this version
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    added: Vec<Entry>,
+    /// Changes to existing features
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    changed: Vec<Entry>,
+    /// Deprecations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    deprecated: Vec<Entry>,
+    /// Changes the removed a feature
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<Entry>,
+    /// Fixes to existing features
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    fixed: Vec<Entry>,
+    /// Security changes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    security: Vec<Entry>,
+    /// Changes for later releases by track name, e.g. `next-major`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tracks: BTreeMap<String, Changes>,
+}
+
+impl From<UnreleasedSource> for Unreleased {
+    fn from(source: UnreleasedSource) -> Self {
+        Unreleased {
+            changes: Changes {
+                added: source.added,
+                changed: source.changed,
+                deprecated: source.deprecated,
+                removed: source.removed,
+                fixed: source.fixed,
+                security: source.security,
+            },
+            tracks: source.tracks,
+        }
+    }
+}
+
+impl From<Unreleased> for UnreleasedSource {
+    fn from(unreleased: Unreleased) -> Self {
+        let Changes {
+            added,
+            changed,
+            deprecated,
+            removed,
+            fixed,
+            security,
+        } = unreleased.changes;
+        UnreleasedSource {
+            added,
+            changed,
+            deprecated,
+            removed,
+            fixed,
+            security,
+            tracks: unreleased.tracks,
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Unreleased {
+    fn schema_name() -> String {
+        "Unreleased".to_string()
+    }
+
+    fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+        <UnreleasedSource as schemars::JsonSchema>::json_schema(generator)
+    }
+}
+
+impl Unreleased {
+    /// Changes on `track`, or for the next release when `None`
+    pub fn track(&self, track: Option<&str>) -> Option<&Changes> {
+        match track {
+            Some(track) => self.tracks.get(track),
+            None => Some(&self.changes),
+        }
+    }
+
+    /// Changes on `track`, or for the next release when `None`, creating the track if needed
+    ///
+    /// Tracks can't be named after a category, such as `fixed`.
+    pub fn track_mut(&mut self, track: Option<&str>) -> anyhow::Result<&mut Changes> {
+        match track {
+            Some(track) if Category::from_key(track).is_some() => Err(anyhow!(
+                "Unreleased tracks can't be named after the {} category",
+                track
+            )),
+            Some(track) => Ok(self.tracks.entry(track.to_string()).or_default()),
+            None => Ok(&mut self.changes),
+        }
+    }
+
+    /// Remove the changes on `track`, or for the next release when `None`, to release them
+    pub fn take(&mut self, track: Option<&str>) -> anyhow::Result<Changes> {
+        match track {
+            Some(track) => self
+                .tracks
+                .remove(track)
+                .ok_or(anyhow!("Unreleased track {} does not exist", track)),
+            None => Ok(std::mem::take(&mut self.changes)),
+        }
+    }
+
+    /// Changes for the next release, followed by each track with its name
+    pub fn all_tracks(&self) -> impl Iterator<Item = (Option<&str>, &Changes)> {
+        std::iter::once((None, &self.changes)).chain(
+            self.tracks
+                .iter()
+                .map(|(name, changes)| (Some(name.as_str()), changes)),
+        )
+    }
+}
+
+impl From<Changes> for Unreleased {
+    fn from(changes: Changes) -> Self {
+        Unreleased {
+            changes,
+            ..Default::default()
+        }
+    }
 }
 
 impl Changes {
     /// Add a new feature
-    pub fn push_added(&mut self, change: String) {
-        self.added.push(change)
+    pub fn push_added(&mut self, change: impl Into<Entry>) {
+        self.added.push(change.into())
     }
     /// Add a change
-    pub fn push_changed(&mut self, change: String) {
-        self.changed.push(change)
+    pub fn push_changed(&mut self, change: impl Into<Entry>) {
+        self.changed.push(change.into())
     }
     /// Add a deprecation
-    pub fn push_deprecated(&mut self, change: String) {
-        self.deprecated.push(change)
+    pub fn push_deprecated(&mut self, change: impl Into<Entry>) {
+        self.deprecated.push(change.into())
     }
     /// Add a fix
-    pub fn push_fixed(&mut self, change: String) {
-        self.fixed.push(change)
+    pub fn push_fixed(&mut self, change: impl Into<Entry>) {
+        self.fixed.push(change.into())
     }
     /// Add a removal change
-    pub fn push_removed(&mut self, change: String) {
-        self.removed.push(change)
+    pub fn push_removed(&mut self, change: impl Into<Entry>) {
+        self.removed.push(change.into())
     }
     /// Add a security change
-    pub fn push_security(&mut self, change: String) {
-        self.security.push(change)
+    pub fn push_security(&mut self, change: impl Into<Entry>) {
+        self.security.push(change.into())
     }
 
-    fn is_empty(&self) -> bool {
+    pub(crate) fn is_empty(&self) -> bool {
         self.added.is_empty()
             && self.changed.is_empty()
             && self.deprecated.is_empty()
@@ -110,143 +347,953 @@ impl Changes {
             && self.security.is_empty()
     }
 
+    /// Get the changes for a single category
+    pub fn get(&self, category: Category) -> &Vec<Entry> {
+        match category {
+            Category::Added => &self.added,
+            Category::Changed => &self.changed,
+            Category::Deprecated => &self.deprecated,
+            Category::Removed => &self.removed,
+            Category::Fixed => &self.fixed,
+            Category::Security => &self.security,
+        }
+    }
+
+    /// Get a mutable reference to the changes for a single category
+    pub fn get_mut(&mut self, category: Category) -> &mut Vec<Entry> {
+        match category {
+            Category::Added => &mut self.added,
+            Category::Changed => &mut self.changed,
+            Category::Deprecated => &mut self.deprecated,
+            Category::Removed => &mut self.removed,
+            Category::Fixed => &mut self.fixed,
+            Category::Security => &mut self.security,
+        }
+    }
+
+    /// Render these changes to Markdown
+    pub fn render(&self, options: &RenderOptions) -> String {
+        render_to_string(|f| self.write_markdown(f, options))
+    }
+
+    fn write_markdown(&self, f: &mut impl Write, options: &RenderOptions) -> std::fmt::Result {
+        for category in &options.categories {
+            self.write_changes_if_exist(f, category.title(), self.get(*category))?;
+        }
+
+        Ok(())
+    }
+
+    /// Breaking changes in the rendered categories, including nested entries
+    pub fn breaking(&self, options: &RenderOptions) -> Vec<&Entry> {
+        fn collect<'a>(entries: &'a [Entry], breaking: &mut Vec<&'a Entry>) {
+            for entry in entries {
+                match entry.breaking {
+                    true => breaking.push(entry),
+                    false => collect(&entry.children, breaking),
+                }
+            }
+        }
+
+        let mut breaking = vec![];
+        for category in &options.categories {
+            collect(self.get(*category), &mut breaking);
+        }
+        breaking
+    }
+
+    // Summary of breaking changes and the migration guide, rendered above everything else
+    fn write_breaking(
+        &self,
+        f: &mut impl Write,
+        migration: Option<&str>,
+        options: &RenderOptions,
+    ) -> std::fmt::Result {
+        if self.breaking(options).is_empty() && migration.is_none() {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        writeln!(f, "### ⚠ Breaking changes")?;
+        writeln!(f)?;
+        self.write_breaking_body(f, migration, options)
+    }
+
+    // Breaking changes as a list, followed by the migration guide
+    fn write_breaking_body(
+        &self,
+        f: &mut impl Write,
+        migration: Option<&str>,
+        options: &RenderOptions,
+    ) -> std::fmt::Result {
+        let breaking = self.breaking(options);
+        for entry in &breaking {
+            entry.write_item(f, 0)?;
+        }
+        if !breaking.is_empty() {
+            writeln!(f)?;
+        }
+        if let Some(migration) = migration {
+            writeln!(f, "{}", migration.trim())?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+
     // Helper to write a block of changes
     fn write_changes_if_exist(
         &self,
-        f: &mut std::fmt::Formatter<'_>,
+        f: &mut impl Write,
         title: &str,
-        changes: &Vec<String>,
+        changes: &Vec<Entry>,
     ) -> std::fmt::Result {
         if !changes.is_empty() {
             writeln!(f)?;
             writeln!(f, "### {}", title)?;
             writeln!(f)?;
             for change in changes {
-                writeln!(f, "- {}", change)?;
+                change.write_item(f, 0)?;
             }
         }
         Ok(())
     }
 }
 
-impl std::fmt::Display for Changelog {
+/// A single change, written as a Markdown string,
+/// or as a map when it carries more details such as a security advisory
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "EntrySource", into = "EntrySource")]
+pub struct Entry {
+    /// Markdown description of the change
+    pub text: String,
+    /// Whether this change breaks compatibility with earlier versions
+    pub breaking: bool,
+    /// Details of the security advisory this change addresses
+    pub advisory: Option<Advisory>,
+    /// Nested changes, rendered as sub-bullets
+    pub children: Vec<Entry>,
+}
+
+impl Entry {
+    /// Whether this entry is only text, and is written as a plain string
+    pub fn is_plain(&self) -> bool {
+        !self.breaking && self.advisory.is_none() && self.children.is_empty()
+    }
+
+    /// Write this entry as a Markdown list item, followed by its children
+    ///
+    /// Continuation lines are indented to stay within the list item
+    pub(crate) fn write_item(&self, f: &mut impl Write, depth: usize) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+        let text = self.to_string();
+        let mut lines = text.trim_end().lines();
+        writeln!(f, "{}- {}", indent, lines.next().unwrap_or_default())?;
+        for line in lines {
+            match line.is_empty() {
+                true => writeln!(f)?,
+                false => writeln!(f, "{}  {}", indent, line)?,
+            }
+        }
+        for child in &self.children {
+            child.write_item(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// A change, written as Markdown text, or as a map with more details
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum EntrySource {
+    Text(String),
+    Detailed(DetailedEntry),
+}
+
+/// A change with more details than its text
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+struct DetailedEntry {
+    /// Markdown description of the change
+    text: String,
+    /// Whether this change breaks compatibility with earlier versions
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    breaking: bool,
+    /// Details of the security advisory this change addresses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    advisory: Option<Advisory>,
+    /// Nested changes, rendered as sub-bullets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Entry>,
+}
+
+impl From<EntrySource> for Entry {
+    fn from(source: EntrySource) -> Self {
+        match source {
+            EntrySource::Text(text) => Entry {
+                text,
+                ..Default::default()
+            },
+            EntrySource::Detailed(DetailedEntry {
+                text,
+                breaking,
+                advisory,
+                children,
+            }) => Entry {
+                text,
+                breaking,
+                advisory,
+                children,
+            },
+        }
+    }
+}
+
+impl From<Entry> for EntrySource {
+    fn from(entry: Entry) -> Self {
+        if entry.is_plain() {
+            return EntrySource::Text(entry.text);
+        }
+        EntrySource::Detailed(DetailedEntry {
+            text: entry.text,
+            breaking: entry.breaking,
+            advisory: entry.advisory,
+            children: entry.children,
+        })
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Entry {
+    fn schema_name() -> String {
+        "Entry".to_string()
+    }
+
+    fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+        <EntrySource as schemars::JsonSchema>::json_schema(generator)
+    }
+}
+
+impl From<String> for Entry {
+    fn from(text: String) -> Self {
+        Entry {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&str> for Entry {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+impl PartialEq<&str> for Entry {
+    fn eq(&self, other: &&str) -> bool {
+        self.is_plain() && self.text == *other
+    }
+}
+
+impl std::fmt::Display for Entry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.advisory {
+            Some(advisory) => advisory.write_markdown(f, &self.text),
+            None => write!(f, "{}", self.text),
+        }
+    }
+}
+
+/// A category of changes, as defined by Keep a Changelog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// New features
+    Added,
+    /// Changes in existing functionality
+    Changed,
+    /// Soon-to-be removed features
+    Deprecated,
+    /// Now removed features
+    Removed,
+    /// Bug fixes
+    Fixed,
+    /// Vulnerabilities
+    Security,
+}
+
+impl Category {
+    /// All categories, in the order they are rendered by default
+    pub const ALL: [Category; 6] = [
+        Category::Added,
+        Category::Changed,
+        Category::Deprecated,
+        Category::Removed,
+        Category::Fixed,
+        Category::Security,
+    ];
+
+    /// The heading used when rendering this category
+    pub fn title(&self) -> &'static str {
+        match self {
+            Category::Added => "Added",
+            Category::Changed => "Changed",
+            Category::Deprecated => "Deprecated",
+            Category::Removed => "Removed",
+            Category::Fixed => "Fixed",
+            Category::Security => "Security",
+        }
+    }
+
+    /// The key used for this category in changelog sources
+    pub fn key(&self) -> &'static str {
+        match self {
+            Category::Added => "added",
+            Category::Changed => "changed",
+            Category::Deprecated => "deprecated",
+            Category::Removed => "removed",
+            Category::Fixed => "fixed",
+            Category::Security => "security",
+        }
+    }
+
+    /// The category using `key` in changelog sources
+    pub fn from_key(key: &str) -> Option<Category> {
+        Category::ALL
+            .into_iter()
+            .find(|category| category.key() == key)
+    }
+}
+
+/// Options controlling how a Changelog is rendered to Markdown
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RenderOptions {
+    /// Categories to render, in order. Categories not listed are omitted
+    pub categories: Vec<Category>,
+    /// Render the `# Revisions` block of compare links
+    pub revisions: bool,
+    /// Only render the latest N versions
+    pub last: Option<usize>,
+    /// Only render versions newer than this version
+    pub since: Option<String>,
+    /// Render versions loaded from `include` archive sources
+    pub archived: bool,
+    /// Link to older releases, when some are not rendered. Defaults to the repository's releases
+    pub older_releases: Option<String>,
+    /// Link `#123`, commit SHAs and `@user` mentions to the repository
+    #[cfg(feature = "markdown")]
+    pub autolink: bool,
+    /// Forge hosting the repository, used for autolinks. Detected from the repository url by default
+    #[cfg(feature = "markdown")]
+    pub forge: Option<Forge>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            categories: Category::ALL.to_vec(),
+            revisions: true,
+            last: None,
+            since: None,
+            archived: false,
+            older_releases: None,
+            #[cfg(feature = "markdown")]
+            autolink: false,
+            #[cfg(feature = "markdown")]
+            forge: None,
+        }
+    }
+}
+
+impl Changelog {
+    /// Render this Changelog to Markdown
+    pub fn render(&self, options: &RenderOptions) -> String {
+        render_to_string(|f| self.write_markdown(f, options))
+    }
+
+    fn write_markdown(&self, f: &mut impl Write, options: &RenderOptions) -> std::fmt::Result {
+        #[cfg(feature = "markdown")]
+        if options.autolink {
+            let mut markdown = String::new();
+            self.write_document(&mut markdown, options)?;
+            return write!(f, "{}", self.autolink(markdown, options));
+        }
+        self.write_document(f, options)
+    }
+
+    /// Apply `RenderOptions::autolink` to Markdown rendered from this changelog
+    #[cfg(feature = "markdown")]
+    pub(crate) fn autolink(&self, markdown: String, options: &RenderOptions) -> String {
+        let forge = options.forge.or_else(|| Forge::detect(&self.repository));
+        match forge {
+            Some(forge) if options.autolink => {
+                autolink::autolink(&markdown, &self.repository, forge)
+            }
+            _ => markdown,
+        }
+    }
+
+    /// Without the `markdown` feature there is no autolinking, and Markdown is left as is
+    #[cfg(not(feature = "markdown"))]
+    pub(crate) fn autolink(&self, markdown: String, _options: &RenderOptions) -> String {
+        markdown
+    }
+
+    fn write_document(&self, f: &mut impl Write, options: &RenderOptions) -> std::fmt::Result {
         writeln!(f, "# {}", self.title)?;
         writeln!(f)?;
         writeln!(f, "{}", self.description)?;
         if !self.description.ends_with("\n") {
             writeln!(f)?;
         }
-        if !self.unreleased.is_empty() {
-            writeln!(f, "## [Unreleased]")?;
-            writeln!(f, "{}", self.unreleased)?;
+        if !self.unreleased.changes.is_empty() || self.next.is_some() {
+            write!(f, "## [Unreleased]")?;
+            if let Some(next) = &self.next {
+                next.write_heading(f)?;
+            }
+            writeln!(f)?;
+            if let Some(theme) = self.next.as_ref().and_then(|next| next.theme.as_ref()) {
+                writeln!(f)?;
+                writeln!(f, "{}", theme.trim())?;
+            }
+            self.unreleased.changes.write_breaking(f, None, options)?;
+            self.unreleased.changes.write_markdown(f, options)?;
+            writeln!(f)?;
+        }
+        for (track, changes) in &self.unreleased.tracks {
+            if changes.is_empty() {
+                continue;
+            }
+            writeln!(f, "## [Unreleased] ({})", track)?;
+            changes.write_breaking(f, None, options)?;
+            changes.write_markdown(f, options)?;
+            writeln!(f)?;
         }
 
-        for version in &self.versions {
-            write!(f, "{}", version)?;
+        let versions = self.rendered_versions(options);
+        for version in &versions {
+            version.write_markdown(f, options)?;
+        }
+        if versions.len() < self.all_versions().count() {
+            let link = options
+                .older_releases
+                .clone()
+                .unwrap_or_else(|| format!("{}/releases", self.repository));
+            writeln!(f, "See [older releases]({}).", link)?;
+            writeln!(f)?;
         }
 
+        if options.revisions {
+            self.write_revisions(f, &versions)?;
+        }
+
+        Ok(())
+    }
+
+    /// Versions selected for rendering by `last`, `since` and `archived`
+    fn rendered_versions(&self, options: &RenderOptions) -> Vec<&Version> {
+        let versions = match options.archived {
+            true => self.all_versions().collect::<Vec<_>>(),
+            false => self.versions.iter().collect(),
+        };
+        versions
+            .into_iter()
+            .filter(|v| match &options.since {
+                Some(since) => self.compare_versions(&v.version, since).is_gt(),
+                None => true,
+            })
+            .take(options.last.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    fn write_revisions(&self, f: &mut impl Write, versions: &[&Version]) -> std::fmt::Result {
         writeln!(f)?;
         writeln!(f, "# Revisions")?;
         writeln!(f)?;
-        match &self.versions[..] {
+        match self.all_versions().next() {
             // We haven't released a version, just link all commits
-            [] => writeln!(f, "- [unreleased] <{}/commits/>", self.repository)?,
+            None => writeln!(f, "- [unreleased] <{}/commits/>", self.repository)?,
 
-            versions @ [.., last] => {
+            Some(latest) => {
                 writeln!(
                     f,
                     "- [unreleased] <{}/compare/{}...HEAD>",
-                    self.repository, versions[0].tag
+                    self.repository, latest.tag
                 )?;
-                for idx in 0..(versions.len() - 1) {
-                    writeln!(
-                        f,
-                        "- [{}] <{}/compare/{}..{}>",
-                        versions[idx].version,
-                        self.repository,
-                        versions[idx + 1].tag,
-                        versions[idx].tag,
-                    )?;
+                for version in versions {
+                    match self.previous_version(version) {
+                        Some(previous) => writeln!(
+                            f,
+                            "- [{}] <{}/compare/{}..{}>",
+                            version.version, self.repository, previous.tag, version.tag,
+                        )?,
+                        // The initial version is a commit url
+                        None => writeln!(
+                            f,
+                            "- [{}] <{}/commits/{}>",
+                            version.version, self.repository, version.tag
+                        )?,
+                    }
                 }
-                // The initial version is a commit url
-                writeln!(
-                    f,
-                    "- [{}] <{}/commits/{}>",
-                    last.version, self.repository, last.tag
-                )?;
             }
         };
 
         Ok(())
     }
+
+    /// Render every breaking change and migration guide after `since`, up to and including `until`
+    ///
+    /// Unreleased changes are included unless `until` is given
+    pub fn render_breaking(
+        &self,
+        since: Option<&str>,
+        until: Option<&str>,
+        options: &RenderOptions,
+    ) -> String {
+        render_to_string(|f| self.write_breaking(f, since, until, options))
+    }
+
+    fn write_breaking(
+        &self,
+        f: &mut impl Write,
+        since: Option<&str>,
+        until: Option<&str>,
+        options: &RenderOptions,
+    ) -> std::fmt::Result {
+        match since {
+            Some(since) => writeln!(f, "# Breaking changes since {}", since)?,
+            None => writeln!(f, "# Breaking changes")?,
+        }
+        writeln!(f)?;
+
+        // Each section ends with a blank line
+        let unreleased = self.unreleased.all_tracks().filter(|_| until.is_none());
+        for (track, changes) in unreleased {
+            if changes.breaking(options).is_empty() {
+                continue;
+            }
+            match track {
+                Some(track) => writeln!(f, "## [Unreleased] ({})", track)?,
+                None => writeln!(f, "## [Unreleased]")?,
+            }
+            writeln!(f)?;
+            changes.write_breaking_body(f, None, options)?;
+        }
+
+        let versions = self.all_versions().filter(|v| {
+            since.is_none_or(|since| self.compare_versions(&v.version, since).is_gt())
+                && until.is_none_or(|until| self.compare_versions(&v.version, until).is_le())
+        });
+        for version in versions {
+            if version.changes.breaking(options).is_empty() && version.migration.is_none() {
+                continue;
+            }
+            writeln!(f, "## {} - {}", version.version, version.date)?;
+            writeln!(f)?;
+            version
+                .changes
+                .write_breaking_body(f, version.migration.as_deref(), options)?;
+        }
+
+        Ok(())
+    }
+
+    /// All releases, newest first, including those loaded from archive sources
+    pub fn all_versions(&self) -> impl Iterator<Item = &Version> {
+        self.versions.iter().chain(&self.archived)
+    }
+
+    /// The version released before `version` on the same release line
+    ///
+    /// This is `Version::previous` if set, otherwise the next entry in `versions`
+    pub fn previous_version(&self, version: &Version) -> Option<&Version> {
+        match &version.previous {
+            Some(previous) => self.all_versions().find(|v| &v.version == previous),
+            None => self
+                .all_versions()
+                .skip_while(|v| v.version != version.version)
+                .nth(1),
+        }
+    }
+
+    /// Add a new release, keeping each release line's history intact
+    ///
+    /// A release follows `release.previous` if set, otherwise the highest lower version.
+    /// Releases on a maintenance line, such as `1.4.3` after `2.0.0`, are inserted directly
+    /// above the version they follow, rather than at the top of `versions`.
+    /// Releasing the planned `next` version clears `next`, as does releasing a new latest
+    /// version when `next` doesn't name one.
+    pub fn add_release(&mut self, release: Version) -> anyhow::Result<()> {
+        if self.all_versions().any(|v| v.version == release.version) {
+            return Err(anyhow!("Version {} already exists!", release.version));
+        }
+        let released = match self.next.as_ref().map(|next| next.version.as_ref()) {
+            Some(Some(planned)) => planned == &release.version,
+            Some(None) => self.latest_version().is_none_or(|latest| {
+                self.compare_versions(&release.version, &latest.version)
+                    .is_gt()
+            }),
+            None => false,
+        };
+        if released {
+            self.next = None;
+        }
+
+        let predecessor = match &release.previous {
+            Some(previous) => Some(
+                self.versions
+                    .iter()
+                    .position(|v| &v.version == previous)
+                    .ok_or(anyhow!("Previous version {} does not exist", previous))?,
+            ),
+            None => self
+                .versions
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| self.compare_versions(&v.version, &release.version).is_lt())
+                .max_by(|(_, a), (_, b)| self.compare_versions(&a.version, &b.version))
+                .map(|(idx, _)| idx),
+        };
+
+        let idx = predecessor.unwrap_or(self.versions.len());
+        if idx > 0 && idx < self.versions.len() {
+            // The version above used to follow the predecessor by position, keep it that way
+            let predecessor = self.versions[idx].version.clone();
+            self.versions[idx - 1].previous.get_or_insert(predecessor);
+        }
+        self.versions.insert(idx, release);
+
+        Ok(())
+    }
+
+    /// The tag for a release of `version`, following `tag_template`
+    ///
+    /// Without a `tag_template`, `default_template` is used, such as the configured `tag-template`,
+    /// and without either the tag is the version itself.
+    /// Placeholders other than `{version}` and a set `{component}` are an error.
+    pub fn tag_for(&self, version: &str, default_template: Option<&str>) -> anyhow::Result<String> {
+        let Some(template) = self.tag_template.as_deref().or(default_template) else {
+            return Ok(version.to_string());
+        };
+        self.check_tag_template(template)?;
+
+        let tag = template.replace("{version}", version);
+        Ok(match &self.component {
+            Some(component) => tag.replace("{component}", component),
+            None => tag,
+        })
+    }
+
+    /// Check every placeholder in a tag template can be filled in
+    pub(crate) fn check_tag_template(&self, template: &str) -> anyhow::Result<()> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            match &rest[start..=start + len] {
+                "{version}" => {}
+                "{component}" if self.component.is_some() => {}
+                "{component}" => {
+                    return Err(anyhow!(
+                        "the tag template {} uses {{component}}, but `component` is not set",
+                        template
+                    ));
+                }
+                placeholder => {
+                    return Err(anyhow!(
+                        "the tag template {} uses the unknown placeholder {}",
+                        template,
+                        placeholder
+                    ));
+                }
+            }
+            rest = &rest[start + len + 1..];
+        }
+        Ok(())
+    }
+
+    /// The versioning scheme, `version_scheme` if set, otherwise the latest release's
+    pub fn scheme(&self) -> Option<Scheme> {
+        self.version_scheme.or_else(|| {
+            let latest = self.all_versions().next()?;
+            VersionNumber::parse(&latest.version).scheme()
+        })
+    }
+
+    /// Parse a version name following `version_scheme`, detecting its scheme if unset
+    ///
+    /// Versions that don't follow `version_scheme` are detected too, `validate` reports them.
+    pub fn version_number(&self, version: &str) -> VersionNumber {
+        self.version_scheme
+            .and_then(|scheme| VersionNumber::parse_as(version, scheme).ok())
+            .unwrap_or_else(|| VersionNumber::parse(version))
+    }
+
+    /// Order version names by precedence, following `version_scheme`
+    pub fn compare_versions(&self, a: &str, b: &str) -> std::cmp::Ordering {
+        self.version_number(a)
+            .cmp_precedence(&self.version_number(b))
+    }
+
+    /// Whether a version name is a pre-release, such as `1.0.0-rc.1`
+    pub fn is_prerelease(&self, version: &str) -> bool {
+        self.version_number(version).is_prerelease()
+    }
+
+    /// The release with the highest precedence, including archived releases
+    pub fn latest_version(&self) -> Option<&Version> {
+        self.all_versions()
+            .max_by(|a, b| self.compare_versions(&a.version, &b.version))
+    }
+
+    /// The version to release the unreleased changes on `track` as, the next release when `None`
+    ///
+    /// Semver and PEP 440 versions are bumped by `bump`, or when `None` by the unreleased changes:
+    /// breaking changes are a major release, additions a minor release, and anything else a patch.
+    /// Calendar versions are `today`, numbered `.1`, `.2` and so on if already released.
+    pub fn next_version(
+        &self,
+        track: Option<&str>,
+        bump: Option<Bump>,
+        today: Date,
+    ) -> anyhow::Result<String> {
+        let changes = self.unreleased.track(track).ok_or(anyhow!(
+            "Unreleased track {} does not exist",
+            track.unwrap_or_default()
+        ))?;
+        let latest = self
+            .latest_version()
+            .map(|latest| self.version_number(&latest.version));
+        if self.scheme() == Some(Scheme::Calver) {
+            if bump.is_some() {
+                return Err(anyhow!(
+                    "Calendar versions are not bumped, they follow the date"
+                ));
+            }
+            return Ok(self.next_calver(latest.as_ref(), today));
+        }
+
+        let Some(latest) = latest else {
+            return Ok("0.1.0".to_string());
+        };
+        let bump = bump.unwrap_or_else(|| {
+            let all = RenderOptions::default();
+            match (changes.breaking(&all).is_empty(), changes.added.is_empty()) {
+                // Until 1.0.0, breaking changes only bump the minor version
+                (false, _) if latest.release().first() == Some(&0) => Bump::Minor,
+                (false, _) => Bump::Major,
+                (true, false) => Bump::Minor,
+                (true, true) => Bump::Patch,
+            }
+        });
+        latest
+            .bump(bump)
+            .map(|next| next.to_string())
+            .ok_or(anyhow!(
+                "Cannot work out the version after {}, give the version to release",
+                latest
+            ))
+    }
+
+    /// `today` as a calendar version, laid out like `latest`
+    fn next_calver(&self, latest: Option<&VersionNumber>, today: Date) -> String {
+        let parts = latest.map_or(3, |latest| latest.release().len());
+        let padded = latest.is_none_or(|latest| {
+            let month = latest.as_str().split('.').nth(1);
+            month.is_some_and(|month| month.len() == 2)
+        });
+        let width = if padded { 2 } else { 1 };
+
+        let mut version = format!("{}.{:0width$}", today.year(), today.month());
+        if parts > 2 {
+            version = format!("{}.{:0width$}", version, today.day());
+        }
+        (0..)
+            .map(|n| match n {
+                0 => version.clone(),
+                n => format!("{}.{}", version, n),
+            })
+            .find(|next| !self.all_versions().any(|v| &v.version == next))
+            .expect("there is always an unreleased version number")
+    }
 }
 
-impl std::fmt::Display for Version {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Render Markdown with a `write_*` function, which cannot fail when writing to a String
+pub(crate) fn render_to_string(write: impl FnOnce(&mut String) -> std::fmt::Result) -> String {
+    let mut out = String::new();
+    write(&mut out).expect("writing to a String cannot fail");
+    out
+}
+
+/// Order version names by precedence, detecting the scheme each follows
+///
+/// Pre-releases such as `1.0.0-rc.1` are ordered before their release.
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    VersionNumber::parse(a).cmp_precedence(&VersionNumber::parse(b))
+}
+
+impl Next {
+    // ` - planned 2.0.0 (target 2025-06)`, following `## [Unreleased]`
+    fn write_heading(&self, f: &mut impl Write) -> std::fmt::Result {
+        match (&self.version, &self.target) {
+            (Some(version), Some(target)) => {
+                write!(f, " - planned {} (target {})", version, target)
+            }
+            (Some(version), None) => write!(f, " - planned {}", version),
+            (None, Some(target)) => write!(f, " - target {}", target),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl Version {
+    /// The version name, parsed as a semver, calver or free-form version
+    pub fn number(&self) -> VersionNumber {
+        VersionNumber::parse(&self.version)
+    }
+
+    /// The release date, parsed from `date`
+    pub fn release_date(&self) -> anyhow::Result<Date> {
+        self.date.parse()
+    }
+
+    /// Render this Version to Markdown
+    pub fn render(&self, options: &RenderOptions) -> String {
+        render_to_string(|f| self.write_markdown(f, options))
+    }
+
+    fn write_markdown(&self, f: &mut impl Write, options: &RenderOptions) -> std::fmt::Result {
         write!(f, "## {} - {}", self.version, self.date)?;
         if let Some(reason) = &self.yanked {
             write!(f, " [YANKED] {}", reason)?;
         }
         writeln!(f)?;
         writeln!(f)?;
+        self.write_body(f, options)
+    }
+
+    // Everything below the version heading
+    pub(crate) fn write_body(
+        &self,
+        f: &mut impl Write,
+        options: &RenderOptions,
+    ) -> std::fmt::Result {
+        self.changes
+            .write_breaking(f, self.migration.as_deref(), options)?;
         if let Some(desc) = &self.description {
             writeln!(f, "{}", desc.trim())?;
         }
         if !self.changes.is_empty() {
-            writeln!(f, "{}", self.changes)?;
+            self.changes.write_markdown(f, options)?;
+            writeln!(f)?;
         }
 
         Ok(())
     }
 }
 
-impl std::fmt::Display for Changes {
+#[cfg(test)]
+impl Version {
+    /// A version released on 2025-01-01 and tagged with its own name, fixing `fixed`
+    pub(crate) fn fixture(version: &str, fixed: &[&str]) -> Version {
+        Version {
+            version: version.to_string(),
+            tag: version.to_string(),
+            date: "2025-01-01".to_string(),
+            changes: Changes {
+                fixed: fixed.iter().map(|s| Entry::from(*s)).collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl std::fmt::Display for Changelog {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.write_changes_if_exist(f, "Added", &self.added)?;
-        self.write_changes_if_exist(f, "Changed", &self.changed)?;
-        self.write_changes_if_exist(f, "Deprecated", &self.deprecated)?;
-        self.write_changes_if_exist(f, "Removed", &self.removed)?;
-        self.write_changes_if_exist(f, "Fixed", &self.fixed)?;
-        self.write_changes_if_exist(f, "Security", &self.security)?;
+        self.write_markdown(f, &RenderOptions::default())
+    }
+}
 
-        Ok(())
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_markdown(f, &RenderOptions::default())
+    }
+}
+
+impl std::fmt::Display for Changes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_markdown(f, &RenderOptions::default())
     }
 }
 
 impl Changelog {
     /// Read a Changelog source file from a filesystem path
     ///
-    /// Encoding is assumed based on extension, this may change in the future
+    /// Encoding is assumed based on extension, falling back to
+    /// detecting the format from the contents for unknown extensions
     pub fn from_path(path: impl Into<std::path::PathBuf>) -> anyhow::Result<Changelog> {
-        let path = path.into();
+        Changelog::from_path_including(&path.into(), &mut vec![])
+    }
 
+    // Read a source, where `including` holds the sources that led to it through `include`
+    fn from_path_including(
+        path: &std::path::Path,
+        including: &mut Vec<std::path::PathBuf>,
+    ) -> anyhow::Result<Changelog> {
         if !path.exists() {
             return Err(anyhow!("no such file {}", path.display()));
         }
 
-        match path.extension().map(|e| e.to_ascii_lowercase()) {
-            Some(e) if e == "yml" || e == "yaml" => {
-                let s = &std::fs::read_to_string(path)?;
-                Self::from_yaml(s)
-            }
-            Some(e) if e == "toml" => {
-                let s = &std::fs::read_to_string(path)?;
-                Self::from_toml(s)
-            }
-            Some(e) if e == "json" => {
-                let s = &std::fs::read_to_string(path)?;
-                Self::from_json(s)
-            }
-            Some(e) => Err(anyhow!("Invalid file extension {}", e.to_string_lossy())),
-            None => Err(anyhow!(
-                "Unable to read {} without an extension",
-                path.display()
-            )),
+        let s = &std::fs::read_to_string(path)?;
+        let mut changelog = match Format::from_extension(path) {
+            Some(format) => format.parse(s),
+            None => Format::detect(s)
+                .ok_or(anyhow!("Unable to detect the format of {}", path.display()))?
+                .parse(s),
+        }
+        .map_err(|err| diagnostic::Diagnostic::attach_file(err, path))?;
+
+        let canonical = path.canonicalize()?;
+        if including.contains(&canonical) {
+            return Err(anyhow!("{} includes itself", path.display()));
+        }
+        including.push(canonical);
+        let base = path.parent().unwrap_or(std::path::Path::new(""));
+        changelog.load_includes_including(base, including)?;
+        including.pop();
+        Ok(changelog)
+    }
+
+    /// Load the versions of each `include` archive source into `archived`
+    ///
+    /// Paths are relative to `base`, the directory holding this changelog's source
+    pub fn load_includes(&mut self, base: &std::path::Path) -> anyhow::Result<()> {
+        self.load_includes_including(base, &mut vec![])
+    }
+
+    fn load_includes_including(
+        &mut self,
+        base: &std::path::Path,
+        including: &mut Vec<std::path::PathBuf>,
+    ) -> anyhow::Result<()> {
+        self.archived.clear();
+        for include in &self.include {
+            let path = base.join(include);
+            let archive = Changelog::from_path_including(&path, including)
+                .with_context(|| format!("Failed to load archive {}", path.display()))?;
+            self.archived.extend(archive.versions);
+            self.archived.extend(archive.archived);
         }
+        Ok(())
     }
 
     /// Parse a Changelog from a YAML string
+    #[cfg(feature = "yaml")]
     pub fn from_yaml(s: &str) -> anyhow::Result<Changelog> {
         let de = serde_yml::Deserializer::from_str(s);
         Ok(serde_path_to_error::deserialize(de)?)
@@ -259,17 +1306,42 @@ impl Changelog {
     }
 
     /// Parse a Changelog from a TOML string
+    #[cfg(feature = "toml")]
     pub fn from_toml(s: &str) -> anyhow::Result<Changelog> {
         let de = toml::Deserializer::new(s);
         Ok(serde_path_to_error::deserialize(de)?)
     }
 
+    /// Parse a Changelog from a JSON5 string
+    #[cfg(feature = "json5")]
+    pub fn from_json5(s: &str) -> anyhow::Result<Changelog> {
+        let mut de = json5::Deserializer::from_str(s)?;
+        Ok(serde_path_to_error::deserialize(&mut de)?)
+    }
+
+    /// Parse a Changelog from a RON string
+    ///
+    /// Accepts both RON maps and structs, as `$schema` is not a valid RON identifier
+    #[cfg(feature = "ron")]
+    pub fn from_ron(s: &str) -> anyhow::Result<Changelog> {
+        let value: serde_json::Value = ron::from_str(s)?;
+        Ok(serde_path_to_error::deserialize(value)?)
+    }
+
+    /// Parse a Changelog from a KDL string
+    #[cfg(feature = "kdl")]
+    pub fn from_kdl(s: &str) -> anyhow::Result<Changelog> {
+        Ok(serde_path_to_error::deserialize(kdl::from_str(s)?)?)
+    }
+
     /// Serialize this Changelog into a YAML string
+    #[cfg(feature = "yaml")]
     pub fn to_yaml(&self) -> anyhow::Result<String> {
         Ok(serde_yml::to_string(&self)?)
     }
 
     /// Serialize this Changelog into a TOML string
+    #[cfg(feature = "toml")]
     pub fn to_toml(&self) -> anyhow::Result<String> {
         Ok(toml::to_string_pretty(&self)?)
     }
@@ -278,11 +1350,35 @@ impl Changelog {
     pub fn to_json(&self) -> anyhow::Result<String> {
         Ok(serde_json::to_string_pretty(&self)? + "\n")
     }
+
+    /// Serialize this Changelog into a JSON5 string
+    ///
+    /// This is plain JSON, which is always valid JSON5
+    #[cfg(feature = "json5")]
+    pub fn to_json5(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&self)? + "\n")
+    }
+
+    /// Serialize this Changelog into a RON string
+    #[cfg(feature = "ron")]
+    pub fn to_ron(&self) -> anyhow::Result<String> {
+        let value = serde_json::to_value(self)?;
+        Ok(ron::ser::to_string_pretty(&value, ron::ser::PrettyConfig::new())? + "\n")
+    }
+
+    /// Serialize this Changelog into a KDL string
+    #[cfg(feature = "kdl")]
+    pub fn to_kdl(&self) -> anyhow::Result<String> {
+        kdl::to_string(&serde_json::to_value(self)?)
+    }
 }
 
 impl Default for Changelog {
     fn default() -> Self {
         Self {
+            schema: Some(migrate::schema_url(SCHEMA_VERSION)),
+            schema_version: Some(SCHEMA_VERSION.to_string()),
+            version_scheme: None,
             title: "Changelog".into(),
             description: r#"All notable changes to this project will be documented in this file.
 
@@ -291,14 +1387,20 @@ and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0
 "#
             .into(),
             repository: "https://github.com/me/my-swanky-project".into(),
+            component: None,
+            tag_template: None,
+            next: None,
             unreleased: Changes {
                 added: vec![
                     "Starting using [changelog-md](https://github.com/kageurufu/changelog-md)"
-                        .to_string(),
+                        .into(),
                 ],
                 ..Default::default()
-            },
+            }
+            .into(),
             versions: vec![],
+            include: vec![],
+            archived: vec![],
         }
     }
 }
@@ -0,0 +1,530 @@
+//! A language server for changelog sources, speaking LSP over stdio
+//!
+//! Documents are synced in full, and re-checked with the same parser and
+//! validator as `changelog-md validate` on every change.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, bail};
+use serde_json::{Value, json};
+
+use crate::config::Config;
+use crate::diagnostic::{Diagnostic, Span};
+use crate::validate::Severity;
+use crate::{Bump, Category, Changelog, Date, Format, Scheme, Version};
+
+/// JSON-RPC error code for requests the server doesn't handle
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// An open changelog source
+struct Document {
+    text: String,
+    format: Format,
+    /// The last version of the document that parsed, kept while it is being edited
+    changelog: Option<Changelog>,
+}
+
+/// Serve the language server protocol until the client sends `exit`
+pub fn serve(input: impl BufRead, output: impl Write, config: Config) -> anyhow::Result<()> {
+    Server {
+        input,
+        output,
+        config,
+        documents: HashMap::new(),
+    }
+    .run()
+}
+
+struct Server<R, W> {
+    input: R,
+    output: W,
+    config: Config,
+    documents: HashMap<String, Document>,
+}
+
+impl<R: BufRead, W: Write> Server<R, W> {
+    fn run(&mut self) -> anyhow::Result<()> {
+        while let Some(message) = self.read()? {
+            let method = message["method"].as_str().unwrap_or_default();
+            let params = &message["params"];
+            if method == "exit" {
+                return Ok(());
+            }
+
+            let result = match method {
+                "initialize" => Some(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "completionProvider": {},
+                        "hoverProvider": true,
+                        "codeActionProvider": true,
+                    },
+                    "serverInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                })),
+                "shutdown" => Some(Value::Null),
+                "textDocument/didOpen" => {
+                    let document = &params["textDocument"];
+                    self.update(uri(document), text(&document["text"]))?;
+                    None
+                }
+                "textDocument/didChange" => {
+                    let changes = params["contentChanges"].as_array();
+                    if let Some(change) = changes.and_then(|changes| changes.last()) {
+                        self.update(uri(&params["textDocument"]), text(&change["text"]))?;
+                    }
+                    None
+                }
+                "textDocument/didClose" => {
+                    let uri = uri(&params["textDocument"]);
+                    self.documents.remove(&uri);
+                    self.publish(&uri, vec![])?;
+                    None
+                }
+                "textDocument/completion" => Some(self.completion(params)),
+                "textDocument/hover" => Some(self.hover(params)),
+                "textDocument/codeAction" => Some(self.code_actions(params)),
+                _ => None,
+            };
+
+            let Some(id) = message.get("id") else {
+                continue;
+            };
+            match result {
+                Some(result) => {
+                    self.write(json!({ "jsonrpc": "2.0", "id": id, "result": result }))?
+                }
+                None => self.write(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": METHOD_NOT_FOUND,
+                        "message": format!("Unsupported method {}", method),
+                    },
+                }))?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a message, or `None` when the client closes the stream
+    fn read(&mut self) -> anyhow::Result<Option<Value>> {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if self.input.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("Content-Length")
+            {
+                length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+        let Some(length) = length else {
+            bail!("Message without a Content-Length header");
+        };
+
+        let mut body = vec![0; length];
+        self.input.read_exact(&mut body)?;
+        Ok(Some(
+            serde_json::from_slice(&body).context("Invalid JSON-RPC message")?,
+        ))
+    }
+
+    fn write(&mut self, message: Value) -> anyhow::Result<()> {
+        let body = message.to_string();
+        write!(
+            self.output,
+            "Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    /// Store a document's new text, and publish its problems
+    fn update(&mut self, uri: String, text: String) -> anyhow::Result<()> {
+        let path = uri_path(&uri);
+        let format = path
+            .as_deref()
+            .and_then(Format::from_extension)
+            .or_else(|| Format::detect(&text))
+            .unwrap_or_default();
+
+        let previous = self.documents.remove(&uri).and_then(|doc| doc.changelog);
+        let mut diagnostics = vec![];
+        let changelog = match format.parse(&text) {
+            Ok(changelog) => {
+                let base = path.as_deref().and_then(|path| path.parent());
+                let problems = changelog.validate(base, self.config.tag_template.as_deref());
+                for problem in problems {
+                    diagnostics.push(Diagnostic::from_problem(&problem, &text, format));
+                }
+                Some(changelog)
+            }
+            Err(err) => {
+                diagnostics.push(Diagnostic::from_error(&err, &text, format));
+                previous
+            }
+        };
+
+        self.publish(&uri, diagnostics)?;
+        self.documents.insert(
+            uri,
+            Document {
+                text,
+                format,
+                changelog,
+            },
+        );
+        Ok(())
+    }
+
+    fn publish(&mut self, uri: &str, diagnostics: Vec<Diagnostic>) -> anyhow::Result<()> {
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let message = match &diagnostic.path {
+                    Some(path) => format!("{}: {}", path, diagnostic.message),
+                    None => diagnostic.message.clone(),
+                };
+                json!({
+                    "range": diagnostic.span.as_ref().map_or_else(
+                        || range((0, 0), (0, 0)),
+                        |span| range(
+                            (span.line - 1, utf16_column(span, span.column)),
+                            (span.line - 1, utf16_column(span, span.end_column)),
+                        ),
+                    ),
+                    "severity": match diagnostic.severity {
+                        Severity::Error => 1,
+                        Severity::Warning => 2,
+                    },
+                    "source": env!("CARGO_PKG_NAME"),
+                    "message": message,
+                })
+            })
+            .collect::<Vec<_>>();
+        self.write(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    fn changelog(&self, params: &Value) -> Option<&Changelog> {
+        self.documents
+            .get(&uri(&params["textDocument"]))?
+            .changelog
+            .as_ref()
+    }
+
+    /// Category names, and the versions already in the changelog
+    fn completion(&self, params: &Value) -> Value {
+        let categories = Category::ALL.iter().map(
+            |category| json!({ "label": category.key(), "kind": 14, "detail": category.title() }),
+        );
+        let versions = self
+            .changelog(params)
+            .into_iter()
+            .flat_map(|changelog| changelog.all_versions())
+            .map(|version| json!({ "label": version.version, "kind": 12, "detail": version.date }));
+        Value::Array(categories.chain(versions).collect())
+    }
+
+    /// Preview the rendered Markdown of the version, or unreleased changes, under the cursor
+    fn hover(&self, params: &Value) -> Value {
+        let Some(document) = self.documents.get(&uri(&params["textDocument"])) else {
+            return Value::Null;
+        };
+        let Some(changelog) = &document.changelog else {
+            return Value::Null;
+        };
+        let position = &params["position"];
+        let line = document
+            .text
+            .lines()
+            .nth(position["line"].as_u64().unwrap_or_default() as usize)
+            .unwrap_or_default();
+        let word = word_at(
+            line,
+            position["character"].as_u64().unwrap_or_default() as usize,
+        );
+
+        let options = self.config.render_options();
+        let markdown = if word == "unreleased" {
+            changelog.unreleased.changes.render(&options)
+        } else if let Some(version) = changelog.all_versions().find(|v| v.version == word) {
+            match changelog.is_prerelease(&version.version) {
+                true => format!("*Pre-release*\n\n{}", version.render(&options)),
+                false => version.render(&options),
+            }
+        } else {
+            return Value::Null;
+        };
+        json!({ "contents": { "kind": "markdown", "value": markdown } })
+    }
+
+    /// Offer to release unreleased changes as the next patch, minor or major version
+    ///
+    /// Each action replaces the whole document with the re-serialized changelog,
+    /// so comments and formatting the source format doesn't keep are lost.
+    fn code_actions(&self, params: &Value) -> Value {
+        let uri = uri(&params["textDocument"]);
+        let Some(document) = self.documents.get(&uri) else {
+            return json!([]);
+        };
+        let Some(changelog) = &document.changelog else {
+            return json!([]);
+        };
+        if changelog.unreleased.changes.is_empty() {
+            return json!([]);
+        }
+
+        let date = Date::today().to_string();
+        let end = end_position(&document.text);
+        let actions = next_versions(changelog)
+            .into_iter()
+            .filter_map(|version| {
+                let mut released = changelog.clone();
+                let changes = std::mem::take(&mut released.unreleased.changes);
+                released
+                    .add_release(Version {
+                        tag: changelog
+                            .tag_for(&version, self.config.tag_template.as_deref())
+                            .ok()?,
+                        date: date.clone(),
+                        changes,
+                        version: version.clone(),
+                        ..Default::default()
+                    })
+                    .ok()?;
+                let text = document.format.to_string(&released).ok()?;
+                Some(json!({
+                    "title": format!("Release unreleased changes as {}", version),
+                    "kind": "source",
+                    "edit": {
+                        "changes": {
+                            uri.clone(): [{ "range": range((0, 0), end), "newText": text }],
+                        },
+                    },
+                }))
+            })
+            .collect();
+        Value::Array(actions)
+    }
+}
+
+fn uri(document: &Value) -> String {
+    text(&document["uri"])
+}
+
+fn text(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+/// The filesystem path of a `file://` uri
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| path.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+fn range(start: (usize, usize), end: (usize, usize)) -> Value {
+    json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+/// The zero-based line and UTF-16 column just past the end of a document
+fn end_position(text: &str) -> (usize, usize) {
+    let (line, last) = text.rsplit_once('\n').map_or((0, text), |(before, last)| {
+        (before.matches('\n').count() + 1, last)
+    });
+    (line, last.chars().map(char::len_utf16).sum())
+}
+
+/// Convert a one-based column in characters to a zero-based column in UTF-16 code units
+fn utf16_column(span: &Span, column: usize) -> usize {
+    span.text
+        .chars()
+        .take(column - 1)
+        .map(char::len_utf16)
+        .sum()
+}
+
+/// The version-like word around a UTF-16 column, without any quotes
+fn word_at(line: &str, character: usize) -> &str {
+    let is_word = |c: char| c.is_alphanumeric() || matches!(c, '.' | '-' | '+' | '_');
+    let mut offset = line.len();
+    let mut units = 0;
+    for (idx, c) in line.char_indices() {
+        if units >= character {
+            offset = idx;
+            break;
+        }
+        units += c.len_utf16();
+    }
+
+    let start = line[..offset]
+        .rfind(|c: char| !is_word(c))
+        .map_or(0, |idx| idx + 1);
+    let end = line[offset..]
+        .find(|c: char| !is_word(c))
+        .map_or(line.len(), |idx| offset + idx);
+    &line[start..end]
+}
+
+/// The next patch, minor and major versions after the highest released version
+///
+/// The planned `next` version comes first, and calendar versioned changelogs only have one
+/// other next version, today's.
+fn next_versions(changelog: &Changelog) -> Vec<String> {
+    let planned = changelog
+        .next
+        .as_ref()
+        .and_then(|next| next.version.clone());
+    let mut versions = planned.into_iter().collect::<Vec<_>>();
+    for version in computed_versions(changelog) {
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+    }
+    versions
+}
+
+fn computed_versions(changelog: &Changelog) -> Vec<String> {
+    let today = Date::today();
+    if changelog.latest_version().is_none() {
+        return vec!["0.1.0".to_string(), "1.0.0".to_string()];
+    }
+    match changelog.scheme() {
+        Some(Scheme::Calver) => changelog
+            .next_version(None, None, today)
+            .into_iter()
+            .collect(),
+        _ => [Bump::Patch, Bump::Minor, Bump::Major]
+            .into_iter()
+            .filter_map(|bump| changelog.next_version(None, Some(bump), today).ok())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn message(value: Value) -> String {
+        let body = value.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    fn responses(output: &[u8]) -> Vec<Value> {
+        let output = String::from_utf8(output.to_vec()).unwrap();
+        output
+            .split("Content-Length: ")
+            .skip(1)
+            .map(|message| serde_json::from_str(message.split_once("\r\n\r\n").unwrap().1).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn serves_a_session() {
+        let uri = "file:///project/CHANGELOG.yml";
+        let text = "title: Changelog\ndescription: ''\nrepository: ''\n\
+                    unreleased:\n  added:\n    - Broken `code\n\
+                    versions:\n  1.0.0:\n    tag: v1.0.0\n    date: 2025-01-01\n    added:\n      - Everything\n";
+        let input = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": { "textDocument": { "uri": uri, "languageId": "yaml", "version": 1, "text": text } },
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "textDocument/hover",
+                "params": { "textDocument": { "uri": uri }, "position": { "line": 7, "character": 3 } },
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "textDocument/codeAction",
+                "params": { "textDocument": { "uri": uri } },
+            }),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ]
+        .map(message)
+        .concat();
+
+        let mut output = vec![];
+        serve(input.as_bytes(), &mut output, Config::default()).unwrap();
+        let responses = responses(&output);
+        assert_eq!(responses.len(), 5);
+
+        let diagnostics = &responses[1]["params"]["diagnostics"];
+        assert_eq!(
+            diagnostics[0]["message"],
+            "unreleased.added[0]: unbalanced backtick"
+        );
+        assert_eq!(
+            diagnostics[0]["range"]["start"],
+            json!({ "line": 5, "character": 6 })
+        );
+
+        let hover = responses[2]["result"]["contents"]["value"]
+            .as_str()
+            .unwrap();
+        assert!(hover.starts_with("## 1.0.0 - 2025-01-01"));
+
+        let actions = responses[3]["result"].as_array().unwrap();
+        assert_eq!(actions[0]["title"], "Release unreleased changes as 1.0.1");
+        assert_eq!(
+            actions[0]["edit"]["changes"][uri][0]["range"]["end"],
+            json!({ "line": 12, "character": 0 })
+        );
+        assert_eq!(end_position("a\nbé😀"), (1, 4));
+        let edit = actions[1]["edit"]["changes"][uri][0]["newText"]
+            .as_str()
+            .unwrap();
+        let released = Changelog::from_yaml(edit).unwrap();
+        assert_eq!(released.versions[0].version, "1.1.0");
+        assert_eq!(released.versions[0].changes.added[0], "Broken `code");
+
+        assert_eq!(
+            responses[4],
+            json!({ "jsonrpc": "2.0", "id": 4, "result": null })
+        );
+    }
+}
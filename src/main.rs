@@ -1,5 +1,17 @@
-use anyhow::{anyhow, bail};
-use changelog_md::{Changelog, Version};
+use anyhow::{Context, anyhow, bail};
+use changelog_md::{
+    Bump, Category, Changelog, Date, Entry, Format, SCHEMA_VERSION, Unreleased, Version,
+    advisory::Package,
+    config::Config,
+    diagnostic::Diagnostic,
+    feed::FeedOptions,
+    merge, migrate,
+    validate::{Problem, Severity},
+};
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 use clap::{Parser, Subcommand, ValueEnum};
 use schemars::schema_for;
@@ -7,10 +19,18 @@ use schemars::schema_for;
 #[derive(Parser)]
 #[command(version, about)]
 pub struct Args {
-    /// Manually specify the path to your changelog
+    /// Manually specify the path to your changelog, or `-` to read from stdin
     #[clap(short, long)]
     changelog: Option<std::path::PathBuf>,
 
+    /// Format of the changelog source, detected from the extension or contents by default
+    #[clap(long, global = true)]
+    input_format: Option<Format>,
+
+    /// How to print errors and validation problems
+    #[clap(long, global = true, default_value = "human")]
+    message_format: MessageFormat,
+
     #[clap(subcommand)]
     command: Command,
 }
@@ -29,11 +49,21 @@ enum Command {
         force: bool,
         #[clap(short, long, default_value = "yaml")]
         format: Format,
+        /// Destination path, or `-` for stdout. Defaults to the source with a new extension
+        #[clap(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Validate a CHANGELOG
     Validate,
 
+    /// Upgrade a CHANGELOG source written for an older version of changelog-md
+    Migrate {
+        /// Write the result here instead of overwriting the source, `-` for stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+
     /// Get the CHANGELOG schema
     Schema {
         /// Destination to write the changelog
@@ -44,19 +74,44 @@ enum Command {
     Add {
         change_type: ChangeType,
         description: String,
+        /// Flag the change as breaking compatibility
+        #[clap(long)]
+        breaking: bool,
+        /// Unreleased track to add the change to, e.g. `next-major`, instead of the next release
+        #[clap(long)]
+        track: Option<String>,
+
+        /// Write the result here instead of overwriting the source, `-` for stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Create a new release from all unreleased changes
     Release {
+        /// Release the changes on this unreleased track, leaving the others pending
+        #[clap(long)]
+        track: Option<String>,
         /// Git Tag, if differs from the version
         #[clap(long)]
         tag: Option<String>,
-        /// Release date, defaults to the current date
+        /// Release date as YYYY-MM-DD, defaults to the current date
         #[clap(long)]
-        date: Option<String>,
-
-        /// New version name
-        version: String,
+        date: Option<Date>,
+        /// Version this release follows, defaults to the highest lower version
+        ///
+        /// Useful when releasing from a maintenance branch
+        #[clap(long)]
+        previous: Option<String>,
+        /// Write the result here instead of overwriting the source, `-` for stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+        /// Part of the version to increment, instead of working it out from the unreleased changes
+        #[clap(long, conflicts_with = "version")]
+        bump: Option<BumpPart>,
+
+        /// New version name, defaults to the planned `next` version,
+        /// or the next version following the versioning scheme
+        version: Option<String>,
         /// Release description
         description: Option<String>,
     },
@@ -67,69 +122,235 @@ enum Command {
         version: String,
         /// Reason for yanking this version
         reason: String,
+
+        /// Write the result here instead of overwriting the source, `-` for stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
     },
 
-    /// Render a CHANGELOG to Markdown
+    /// Git merge driver, merging changelog sources structurally
+    ///
+    /// Configured by `install-merge-driver` as `changelog-md merge-driver %O %A %B %P`
+    MergeDriver {
+        /// Common ancestor version
+        base: PathBuf,
+        /// Current version, the merge result is written here
+        ours: PathBuf,
+        /// Version being merged in
+        theirs: PathBuf,
+        /// Original path of the changelog, used to detect the format
+        path: Option<PathBuf>,
+    },
+
+    /// Configure git to merge the changelog source with `merge-driver`
+    InstallMergeDriver,
+
+    /// Render a CHANGELOG to Markdown, or an Atom or RSS feed
     Render {
-        /// Destination path
+        /// Output format
+        #[clap(short, long, default_value = "markdown")]
+        format: RenderFormat,
+        /// Maximum number of versions to include in a feed
+        #[clap(long)]
+        max_entries: Option<usize>,
+        /// Only render the latest N versions
+        #[clap(long)]
+        last: Option<usize>,
+        /// Only render versions newer than this version
+        #[clap(long)]
+        since: Option<String>,
+        /// Link `#123`, commit SHAs and `@user` mentions to the repository
+        #[clap(long)]
+        autolink: bool,
+        /// Check the destination is up to date instead of writing it
+        #[clap(long)]
+        check: bool,
+        /// Re-render whenever the changelog source changes
+        #[clap(long, conflicts_with = "check")]
+        watch: bool,
+        /// Write one Markdown page per release into this directory, with an index and an unreleased page
+        #[clap(long, value_name = "DIR", conflicts_with_all = ["check", "watch", "destination"])]
+        split: Option<PathBuf>,
+
+        /// Destination path, or `-` for stdout
         destination: Option<std::path::PathBuf>,
     },
-}
 
-#[derive(Debug, Clone, ValueEnum)]
-enum ChangeType {
-    Added,
-    Changed,
-    Deprecated,
-    Removed,
-    Fixed,
-    Security,
+    /// Move older versions into archive sources, loaded through `include`
+    ///
+    /// Archives are written next to the changelog, e.g. `CHANGELOG-1.x.yml` or `CHANGELOG-2023.yml`
+    Archive {
+        /// Archive versions lower than this version
+        #[clap(long, required_unless_present = "keep")]
+        before: Option<String>,
+        /// Archive all but the latest N versions
+        #[clap(long, conflicts_with = "before")]
+        keep: Option<usize>,
+        /// Write one archive per major version, or per release year
+        #[clap(long, default_value = "major")]
+        by: ArchiveBy,
+    },
+
+    /// Collect breaking changes and migration guides across a range of versions
+    Breaking {
+        /// Only include versions newer than this version
+        #[clap(long)]
+        since: Option<String>,
+        /// Only include versions up to and including this version, excluding unreleased changes
+        #[clap(long)]
+        until: Option<String>,
+
+        /// Destination path, or `-` for stdout
+        #[clap(default_value = "-")]
+        destination: PathBuf,
+    },
+
+    /// Aggregate the changes released between two versions, such as for upgrade notes
+    ///
+    /// Each change is annotated with the version it was released in, and yanked versions are called out
+    Between {
+        /// Version upgraded from, its own changes are excluded
+        since: String,
+        /// Version upgraded to, its changes are included
+        until: String,
+        /// Output format
+        #[clap(short, long, default_value = "markdown")]
+        format: BetweenFormat,
+
+        /// Destination path, or `-` for stdout
+        #[clap(default_value = "-")]
+        destination: PathBuf,
+    },
+
+    /// Run a language server for changelog sources over stdio
+    ///
+    /// Publishes validation problems, completes categories and versions,
+    /// previews versions on hover and offers to release unreleased changes
+    Lsp,
+
+    /// Export the security advisories of released versions
+    ///
+    /// Writes one file per advisory, named after its identifier
+    Advisories {
+        /// Export format
+        #[clap(short, long, default_value = "osv")]
+        format: AdvisoryFormat,
+        /// Affected package name, defaults to the last segment of the repository url
+        #[clap(long)]
+        package: Option<String>,
+        /// Affected package ecosystem, as named by OSV
+        #[clap(long, default_value = "crates.io")]
+        ecosystem: String,
+
+        /// Destination directory, or `-` to write a JSON array of OSV advisories to stdout
+        #[clap(default_value = "advisories")]
+        destination: PathBuf,
+    },
 }
 
-#[derive(Debug, Default, Clone, ValueEnum)]
-enum Format {
-    #[default]
-    #[value(alias("yml"))]
-    Yaml,
-    Toml,
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum MessageFormat {
+    /// Annotated snippets of the offending source
+    Human,
+    /// One JSON object per line on stdout, for editors and CI annotations
     Json,
 }
 
-impl Format {
-    pub fn extension(&self) -> &str {
+impl MessageFormat {
+    fn emit(&self, diagnostic: &Diagnostic) {
+        self.emit_in_context(&[], diagnostic)
+    }
+
+    /// Emit a diagnostic below the context it was found in, such as the archive being loaded
+    fn emit_in_context(&self, context: &[String], diagnostic: &Diagnostic) {
+        let rendered = context
+            .iter()
+            .map(|context| format!("{}:\n", context))
+            .collect::<String>()
+            + &diagnostic.to_string();
         match self {
-            Format::Yaml => "yml",
-            Format::Toml => "toml",
-            Format::Json => "json",
+            MessageFormat::Human => eprintln!("{}", rendered),
+            MessageFormat::Json => {
+                let mut json = diagnostic.to_json();
+                if !context.is_empty() {
+                    json["context"] = context.into();
+                    json["rendered"] = rendered.into();
+                }
+                println!("{}", json)
+            }
         }
     }
+}
 
-    pub fn to_string(&self, seed: &Changelog) -> anyhow::Result<String> {
-        match self {
-            Format::Yaml => seed.to_yaml(),
-            Format::Toml => seed.to_toml(),
-            Format::Json => seed.to_json(),
+#[derive(Debug, Clone, ValueEnum)]
+enum AdvisoryFormat {
+    /// OSV JSON, as used by osv.dev and GitHub
+    Osv,
+    /// RustSec advisory database TOML
+    Rustsec,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+enum BetweenFormat {
+    #[value(alias("md"))]
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+enum ArchiveBy {
+    Major,
+    Year,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+enum BumpPart {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl From<BumpPart> for Bump {
+    fn from(value: BumpPart) -> Self {
+        match value {
+            BumpPart::Major => Bump::Major,
+            BumpPart::Minor => Bump::Minor,
+            BumpPart::Patch => Bump::Patch,
         }
     }
 }
 
-impl TryFrom<&std::path::PathBuf> for Format {
-    type Error = anyhow::Error;
+#[derive(Debug, Clone, ValueEnum)]
+enum ChangeType {
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+    Fixed,
+    Security,
+}
 
-    fn try_from(value: &std::path::PathBuf) -> Result<Self, Self::Error> {
-        match value.extension().map(|v| v.to_ascii_lowercase()) {
-            Some(ext) if ext == "yml" || ext == "yaml" => Ok(Format::Yaml),
-            Some(ext) if ext == "json" => Ok(Format::Json),
-            Some(ext) if ext == "toml" => Ok(Format::Toml),
-            Some(ext) => Err(anyhow!("Unknown file extension {}", ext.to_string_lossy())),
-            _ => Err(anyhow!(
-                "Missing file extension for {}",
-                value.to_string_lossy()
-            )),
+impl From<ChangeType> for Category {
+    fn from(value: ChangeType) -> Self {
+        match value {
+            ChangeType::Added => Category::Added,
+            ChangeType::Changed => Category::Changed,
+            ChangeType::Deprecated => Category::Deprecated,
+            ChangeType::Removed => Category::Removed,
+            ChangeType::Fixed => Category::Fixed,
+            ChangeType::Security => Category::Security,
         }
     }
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+enum RenderFormat {
+    #[value(alias("md"))]
+    Markdown,
+    Atom,
+    Rss,
+}
+
 fn autodetect_source() -> anyhow::Result<std::path::PathBuf> {
     let entries = std::fs::read_dir(".")
         .unwrap()
@@ -138,11 +359,7 @@ fn autodetect_source() -> anyhow::Result<std::path::PathBuf> {
             p.file_stem()
                 .is_some_and(|s| s.eq_ignore_ascii_case("changelog"))
         })
-        .filter(|p| {
-            p.extension()
-                .map(|s| s.to_ascii_lowercase())
-                .is_some_and(|ext| ext == "yml" || ext == "yaml" || ext == "toml" || ext == "json")
-        })
+        .filter(|p| Format::from_extension(p).is_some())
         .collect::<Vec<_>>();
 
     if entries.len() > 1 {
@@ -155,31 +372,257 @@ fn autodetect_source() -> anyhow::Result<std::path::PathBuf> {
     }
 }
 
-/// Search upwards for a .git/config with `[remote "origin"] url = ...`
-fn get_git_remote() -> Option<String> {
-    if let Ok(path) = std::env::current_dir() {
-        for path in path.ancestors() {
-            let git_config = path.join(".git/config");
-            if git_config.exists() {
-                if let Ok(contents) = std::fs::read_to_string(git_config) {
-                    if let Ok(conf) = ini::Ini::load_from_str(&contents) {
-                        if let Some(section) = conf.section(Some(r#"remote "origin""#)) {
-                            if let Some(url) = section.get("url") {
-                                return Some(url.to_string());
-                            }
-                        }
-                    }
+/// Search upwards for a .git/config with `[remote "<remote>"] url = ...`
+fn get_git_remote(remote: &str) -> Option<String> {
+    let path = std::env::current_dir().ok()?;
+    for path in path.ancestors() {
+        let git_config = path.join(".git/config");
+        if git_config.exists()
+            && let Ok(contents) = std::fs::read_to_string(git_config)
+            && let Ok(conf) = ini::Ini::load_from_str(&contents)
+            && let Some(section) = conf.section(Some(format!(r#"remote "{}""#, remote)))
+            && let Some(url) = section.get("url")
+        {
+            return Some(url.to_string());
+        }
+    }
+    None
+}
+
+/// `-` is used in place of a path for stdin and stdout
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Read a changelog source from a file or stdin, and determine its format
+fn read_source(path: &Path, input_format: Option<Format>) -> anyhow::Result<(String, Format)> {
+    let contents = if is_stdio(path) {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else if path.exists() {
+        std::fs::read_to_string(path)?
+    } else {
+        bail!("no such file {}", path.display());
+    };
+
+    let format = input_format
+        .or_else(|| Format::from_extension(path))
+        .or_else(|| Format::detect(&contents))
+        .with_context(|| {
+            format!(
+                "Unable to detect the format of {}, try --input-format",
+                path.display()
+            )
+        })?;
+
+    Ok((contents, format))
+}
+
+/// Load a changelog from a file or stdin, along with any archives it includes
+fn load(path: &Path, input_format: Option<Format>) -> anyhow::Result<(Changelog, Format)> {
+    let (changelog, _, format) = load_source(path, input_format)?;
+    Ok((changelog, format))
+}
+
+/// Load a changelog like [`load`], also returning its source for locating problems
+fn load_source(
+    path: &Path,
+    input_format: Option<Format>,
+) -> anyhow::Result<(Changelog, String, Format)> {
+    let (contents, format) = read_source(path, input_format)?;
+    let mut changelog = format
+        .parse(&contents)
+        .map_err(|err| Diagnostic::attach_file(err, path))?;
+    changelog.load_includes(source_dir(path))?;
+    Ok((changelog, contents, format))
+}
+
+/// The directory relative paths in a changelog source are resolved against
+fn source_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !is_stdio(path) => parent,
+        _ => Path::new(""),
+    }
+}
+
+/// The archive a version belongs in
+///
+/// Archives by major version use the first release component of the changelog's scheme,
+/// which is the year for calendar versions
+fn archive_key(changelog: &Changelog, version: &Version, by: &ArchiveBy) -> anyhow::Result<String> {
+    match by {
+        ArchiveBy::Major => changelog
+            .version_number(&version.version)
+            .release()
+            .first()
+            .map(|major| format!("{}.x", major)),
+        ArchiveBy::Year => version
+            .date
+            .get(..4)
+            .filter(|year| year.parse::<u16>().is_ok())
+            .map(str::to_string),
+    }
+    .with_context(|| format!("Unable to pick an archive for version {}", version.version))
+}
+
+/// Pin `previous` wherever a version no longer sits directly above its predecessor
+///
+/// The last version of each source is followed by the next included archive, so is left as is
+fn pin_previous(versions: &mut [Version], predecessors: &HashMap<String, String>) {
+    for idx in 1..versions.len() {
+        let next = versions[idx].version.clone();
+        let version = &mut versions[idx - 1];
+        if version.previous.is_none()
+            && let Some(predecessor) = predecessors.get(&version.version)
+            && *predecessor != next
+        {
+            version.previous = Some(predecessor.clone());
+        }
+    }
+}
+
+/// Write to a file, or stdout
+fn write_output(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if is_stdio(path) {
+        print!("{}", contents);
+    } else {
+        std::fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+/// Render a changelog source in the given format
+fn render(
+    changelog_file: &Path,
+    input_format: Option<Format>,
+    format: &RenderFormat,
+    options: &FeedOptions,
+) -> anyhow::Result<String> {
+    let (changelog, _) = load(changelog_file, input_format)?;
+
+    match format {
+        RenderFormat::Markdown => Ok(changelog.render(&options.render)),
+        RenderFormat::Atom => changelog.to_atom(options),
+        RenderFormat::Rss => changelog.to_rss(options),
+    }
+}
+
+/// Call `on_change` after any of `paths` change, debouncing bursts of events
+///
+/// Parent directories are watched so that editors replacing files on save are noticed.
+fn watch_paths(paths: &[PathBuf], mut on_change: impl FnMut()) -> anyhow::Result<()> {
+    let paths = paths
+        .iter()
+        .map(std::path::absolute)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut debouncer =
+        notify_debouncer_mini::new_debouncer(std::time::Duration::from_millis(250), tx)?;
+    for path in &paths {
+        let dir = path.parent().unwrap_or(path);
+        debouncer.watcher().watch(
+            dir,
+            notify_debouncer_mini::notify::RecursiveMode::NonRecursive,
+        )?;
+    }
+
+    for events in rx {
+        match events {
+            Ok(events) => {
+                if events
+                    .iter()
+                    .any(|event| paths.iter().any(|path| event.path.starts_with(path)))
+                {
+                    on_change();
                 }
             }
+            Err(e) => eprintln!("Error watching files: {}", e),
         }
     }
-    None
+
+    Ok(())
 }
 
-fn main() -> anyhow::Result<()> {
+/// Parse the three sides of a merge, and merge them if they parse
+fn merge_sources(
+    base: &Path,
+    ours: &Path,
+    theirs: &Path,
+    path: Option<&Path>,
+) -> anyhow::Result<(Format, Result<Changelog, Vec<merge::Conflict>>)> {
+    let ours_contents = std::fs::read_to_string(ours)?;
+    let format = match path.and_then(Format::from_extension) {
+        Some(format) => format,
+        None => Format::detect(&ours_contents)
+            .ok_or(anyhow!("Unable to detect the format of {}", ours.display()))?,
+    };
+    let parse = |side: &str, contents: &str| {
+        format
+            .parse(contents)
+            .with_context(|| format!("Failed to parse {}", side))
+    };
+
+    let merged = merge::merge(
+        &parse("base", &std::fs::read_to_string(base)?)?,
+        &parse("ours", &ours_contents)?,
+        &parse("theirs", &std::fs::read_to_string(theirs)?)?,
+    );
+    Ok((format, merged))
+}
+
+/// Leave the usual conflict markers in `ours` for the user to resolve
+fn merge_file(base: &Path, ours: &Path, theirs: &Path) -> anyhow::Result<()> {
+    std::process::Command::new("git")
+        .arg("merge-file")
+        .args(["-L", "ours", "-L", "base", "-L", "theirs"])
+        .args([ours, base, theirs])
+        .status()?;
+    Ok(())
+}
+
+/// Search upwards for the directory containing `.git`
+fn find_repository_root() -> anyhow::Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    cwd.ancestors()
+        .find(|path| path.join(".git").exists())
+        .map(Path::to_path_buf)
+        .ok_or(anyhow!("Not inside a git repository"))
+}
+
+fn main() -> std::process::ExitCode {
     let args = Args::parse();
+    let message_format = args.message_format;
+    match run(args) {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => {
+            // Errors wrapping a diagnostic are context, outermost first
+            let mut context = vec![];
+            let diagnostic = err.chain().find_map(|err| {
+                let diagnostic = err.downcast_ref::<Diagnostic>();
+                if diagnostic.is_none() {
+                    context.push(err.to_string());
+                }
+                diagnostic
+            });
+            match diagnostic {
+                Some(diagnostic) => message_format.emit_in_context(&context, diagnostic),
+                None => {
+                    message_format.emit(&Diagnostic::new(Severity::Error, format!("{:#}", err)))
+                }
+            }
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> anyhow::Result<()> {
+    let input_format = args.input_format;
+    let message_format = args.message_format;
+    let config = Config::discover(&std::env::current_dir()?)?.unwrap_or_default();
     let changelog_file = {
-        match args.changelog {
+        match args.changelog.or_else(|| config.changelog_path()) {
             Some(filename) => Ok(filename),
             None => autodetect_source(),
         }
@@ -194,54 +637,463 @@ fn main() -> anyhow::Result<()> {
                 Err(anyhow!("{} already exists", filename.display()))
             } else {
                 let mut seed = Changelog::default();
-                if let Some(url) = get_git_remote() {
+                if let Some(url) = get_git_remote(config.remote.as_deref().unwrap_or("origin")) {
                     seed.repository = url;
                 };
                 let seed = format.to_string(&seed)?;
                 eprintln!("Writing initial {}", filename.display());
-                std::fs::write(filename, seed)?;
+                write_output(&filename, &seed)?;
 
                 Ok(())
             }
         }
 
-        Command::Convert { format, force } => {
+        Command::Convert {
+            format,
+            force,
+            output,
+        } => {
             let changelog_file = changelog_file?;
-            let destination = changelog_file.with_extension(format.extension());
+            let destination = output.unwrap_or_else(|| match is_stdio(&changelog_file) {
+                true => changelog_file.clone(),
+                false => changelog_file.with_extension(format.extension()),
+            });
 
             if destination.exists() && !force {
                 return Err(anyhow!("{} already exists", destination.display()));
             }
 
-            let changelog = Changelog::from_path(&changelog_file)?;
+            let (changelog, _) = load(&changelog_file, input_format)?;
             let changelog = format.to_string(&changelog)?;
             eprintln!(
                 "Converting {} to {}",
                 changelog_file.display(),
                 destination.display()
             );
-            std::fs::write(destination, changelog)?;
+            write_output(&destination, &changelog)?;
 
             Ok(())
         }
 
-        Command::Render { destination } => {
+        Command::Render {
+            format,
+            max_entries,
+            last,
+            since,
+            autolink,
+            check,
+            watch,
+            split,
+            destination,
+        } => {
             let changelog_file = changelog_file?;
-            let changelog = Changelog::from_path(&changelog_file)?;
+            let destination = destination.unwrap_or_else(|| match format {
+                _ if is_stdio(&changelog_file) => changelog_file.clone(),
+                RenderFormat::Markdown => config
+                    .output_path()
+                    .unwrap_or_else(|| changelog_file.with_extension("md")),
+                RenderFormat::Atom => changelog_file.with_extension("atom.xml"),
+                RenderFormat::Rss => changelog_file.with_extension("rss.xml"),
+            });
+            let mut options = FeedOptions {
+                max_entries,
+                render: config.render_options(),
+            };
+            if last.is_some() {
+                options.render.last = last;
+            }
+            if since.is_some() {
+                options.render.since = since;
+            }
+            if autolink {
+                options.render.autolink = true;
+            }
+            if let Some(dir) = split {
+                let RenderFormat::Markdown = format else {
+                    bail!("--split only renders Markdown");
+                };
+                let (changelog, _) = load(&changelog_file, input_format)?;
+                let pages = changelog.render_split(&options.render, &config.split)?;
+                std::fs::create_dir_all(&dir)?;
+                for page in &pages {
+                    std::fs::write(dir.join(&page.file_name), &page.contents)?;
+                }
+                eprintln!("Rendered {} pages to {}", pages.len(), dir.display());
+                return Ok(());
+            }
+            let render = || render(&changelog_file, input_format, &format, &options);
+
+            if watch {
+                if is_stdio(&changelog_file) || is_stdio(&destination) {
+                    bail!("--watch cannot be used with stdin or stdout");
+                }
+
+                // Archives are watched too, the list is read once on startup
+                let (changelog, _) = load(&changelog_file, input_format)?;
+                let mut paths = vec![changelog_file.clone()];
+                paths.extend(
+                    changelog
+                        .include
+                        .iter()
+                        .map(|include| source_dir(&changelog_file).join(include)),
+                );
+
+                let render_to_destination = || {
+                    match render() {
+                        Ok(rendered) => match std::fs::write(&destination, rendered) {
+                            Ok(()) => eprintln!(
+                                "Rendered {} to {}",
+                                changelog_file.display(),
+                                destination.display()
+                            ),
+                            Err(e) => eprintln!("Error writing {}: {}", destination.display(), e),
+                        },
+                        Err(e) => eprintln!("Error: {:?}", e),
+                    };
+                };
+
+                render_to_destination();
+                eprintln!("Watching {} for changes", changelog_file.display());
+                return watch_paths(&paths, render_to_destination);
+            }
+
+            let rendered = render()?;
 
-            let destination = destination.unwrap_or_else(|| changelog_file.with_extension("md"));
+            if check {
+                let current = std::fs::read_to_string(&destination).unwrap_or_default();
+                if current == rendered {
+                    eprintln!("{} is up to date", destination.display());
+                    return Ok(());
+                }
+
+                let diff = similar::TextDiff::from_lines(&current, &rendered);
+                print!(
+                    "{}",
+                    diff.unified_diff().header(
+                        &destination.display().to_string(),
+                        &format!("{} (rendered)", destination.display())
+                    )
+                );
+                bail!(
+                    "{} is out of date, run `changelog-md render` to update it",
+                    destination.display()
+                );
+            }
 
             eprintln!(
                 "Rendering {} to {}",
                 changelog_file.display(),
                 destination.display()
             );
-            Ok(std::fs::write(destination, format!("{}", &changelog))?)
+            write_output(&destination, &rendered)
+        }
+
+        Command::Archive { before, keep, by } => {
+            let changelog_file = changelog_file?;
+            if is_stdio(&changelog_file) {
+                bail!(
+                    "archive writes archives next to the changelog, and cannot be used with stdin"
+                );
+            }
+            let (mut changelog, format) = load(&changelog_file, input_format)?;
+
+            let predecessors = changelog
+                .versions
+                .iter()
+                .filter_map(|v| {
+                    let previous = changelog.previous_version(v)?;
+                    Some((v.version.clone(), previous.version.clone()))
+                })
+                .collect::<HashMap<_, _>>();
+
+            let mut kept = vec![];
+            let mut archives: Vec<(String, Vec<Version>)> = vec![];
+            for (idx, version) in std::mem::take(&mut changelog.versions)
+                .into_iter()
+                .enumerate()
+            {
+                let archive = match (&before, keep) {
+                    (Some(before), _) => {
+                        changelog.compare_versions(&version.version, before).is_lt()
+                    }
+                    (None, Some(keep)) => idx >= keep,
+                    (None, None) => unreachable!("clap requires --before or --keep"),
+                };
+                if !archive {
+                    kept.push(version);
+                    continue;
+                }
+                let key = archive_key(&changelog, &version, &by)?;
+                match archives.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, versions)) => versions.push(version),
+                    None => archives.push((key, vec![version])),
+                }
+            }
+            if archives.is_empty() {
+                eprintln!("No versions to archive");
+                return Ok(());
+            }
+            pin_previous(&mut kept, &predecessors);
+            changelog.versions = kept;
+
+            let stem = changelog_file
+                .file_stem()
+                .context("The changelog path has no file name")?
+                .to_string_lossy();
+            let extension = changelog_file
+                .extension()
+                .map(|ext| ext.to_string_lossy())
+                .unwrap_or(format.extension().into());
+
+            let mut include = vec![];
+            for (key, mut versions) in archives {
+                pin_previous(&mut versions, &predecessors);
+                let name = format!("{}-{}.{}", stem, key, extension);
+                let path = source_dir(&changelog_file).join(&name);
+
+                let (archive, archive_format) = if path.exists() {
+                    let mut archive = Changelog::from_path(&path)?;
+                    archive.versions.splice(0..0, versions);
+                    (archive, Format::from_path(&path)?)
+                } else {
+                    let archive = Changelog {
+                        title: format!("{} ({})", changelog.title, key),
+                        description: format!("Older releases of {}.\n", changelog.title),
+                        repository: changelog.repository.clone(),
+                        unreleased: Unreleased::default(),
+                        versions,
+                        ..Default::default()
+                    };
+                    (archive, format)
+                };
+                write_output(&path, &archive_format.to_string(&archive)?)?;
+                eprintln!("Archived to {}", path.display());
+                include.push(name);
+            }
+
+            for existing in changelog.include.drain(..) {
+                if !include.contains(&existing) {
+                    include.push(existing);
+                }
+            }
+            changelog.include = include;
+
+            write_output(&changelog_file, &format.to_string(&changelog)?)
+        }
+
+        Command::Breaking {
+            since,
+            until,
+            destination,
+        } => {
+            let (changelog, _) = load(&changelog_file?, input_format)?;
+            let rendered = changelog.render_breaking(
+                since.as_deref(),
+                until.as_deref(),
+                &config.render_options(),
+            );
+            write_output(&destination, &rendered)
+        }
+
+        Command::Between {
+            since,
+            until,
+            format,
+            destination,
+        } => {
+            let (changelog, _) = load(&changelog_file?, input_format)?;
+            let between = changelog.between(&since, &until)?;
+            let options = config.render_options();
+            let rendered = match format {
+                BetweenFormat::Markdown => between.render(&options),
+                BetweenFormat::Json => {
+                    serde_json::to_string_pretty(&between.to_json(&options)?)? + "\n"
+                }
+            };
+            write_output(&destination, &rendered)
+        }
+
+        Command::Advisories {
+            format,
+            package,
+            ecosystem,
+            destination,
+        } => {
+            let (changelog, _) = load(&changelog_file?, input_format)?;
+            let package = Package {
+                name: package.unwrap_or_else(|| {
+                    changelog
+                        .repository
+                        .trim_end_matches('/')
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string()
+                }),
+                ecosystem,
+            };
+            let advisories = changelog.advisories();
+
+            if is_stdio(&destination) {
+                let AdvisoryFormat::Osv = format else {
+                    bail!("RustSec advisories can only be written to a directory");
+                };
+                let osv = advisories
+                    .iter()
+                    .map(|advisory| advisory.to_osv(&package))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                println!("{}", serde_json::to_string_pretty(&osv)?);
+                return Ok(());
+            }
+
+            std::fs::create_dir_all(&destination)?;
+            for advisory in &advisories {
+                let (contents, extension) = match format {
+                    AdvisoryFormat::Osv => (
+                        serde_json::to_string_pretty(&advisory.to_osv(&package)?)? + "\n",
+                        "json",
+                    ),
+                    AdvisoryFormat::Rustsec => (advisory.to_rustsec(&package)?, "toml"),
+                };
+                let path = destination.join(format!("{}.{}", advisory.advisory.id, extension));
+                std::fs::write(&path, contents)?;
+            }
+            eprintln!(
+                "Exported {} advisories to {}",
+                advisories.len(),
+                destination.display()
+            );
+            Ok(())
+        }
+
+        Command::MergeDriver {
+            base,
+            ours,
+            theirs,
+            path,
+        } => match merge_sources(&base, &ours, &theirs, path.as_deref()) {
+            Ok((format, Ok(merged))) => Ok(std::fs::write(&ours, format.to_string(&merged)?)?),
+            Ok((_, Err(conflicts))) => {
+                for conflict in &conflicts {
+                    eprintln!("Conflict in {}", conflict);
+                }
+                merge_file(&base, &ours, &theirs)?;
+                bail!("Unable to merge {} conflicting changes", conflicts.len());
+            }
+            Err(err) => {
+                eprintln!("{:#}", err);
+                merge_file(&base, &ours, &theirs)?;
+                bail!("Unable to merge the changelog structurally");
+            }
+        },
+
+        Command::InstallMergeDriver => {
+            let root = find_repository_root()?;
+            let changelog_file = std::path::absolute(changelog_file?)?;
+            let pattern = changelog_file
+                .strip_prefix(std::path::absolute(&root)?)
+                .map_err(|_| anyhow!("{} is outside the repository", changelog_file.display()))?
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            let attribute = format!("{} merge=changelog-md", pattern);
+            let gitattributes = root.join(".gitattributes");
+            let mut contents = std::fs::read_to_string(&gitattributes).unwrap_or_default();
+            if !contents.lines().any(|line| line.trim() == attribute) {
+                if !contents.is_empty() && !contents.ends_with('\n') {
+                    contents.push('\n');
+                }
+                contents += &attribute;
+                contents.push('\n');
+                std::fs::write(&gitattributes, contents)?;
+                eprintln!("Added `{}` to {}", attribute, gitattributes.display());
+            }
+
+            for (key, value) in [
+                ("merge.changelog-md.name", "changelog-md structural merge"),
+                (
+                    "merge.changelog-md.driver",
+                    "changelog-md merge-driver %O %A %B %P",
+                ),
+            ] {
+                let status = std::process::Command::new("git")
+                    .current_dir(&root)
+                    .args(["config", "--local", key, value])
+                    .status()?;
+                if !status.success() {
+                    bail!("Failed to set git config {}", key);
+                }
+            }
+            eprintln!("Installed the changelog-md merge driver");
+
+            Ok(())
+        }
+
+        Command::Lsp => {
+            changelog_md::lsp::serve(std::io::stdin().lock(), std::io::stdout(), config)
         }
 
         Command::Validate => {
-            Changelog::from_path(&changelog_file?)?;
-            println!("No issues found");
+            let changelog_file = changelog_file?;
+            let (changelog, source, format) = load_source(&changelog_file, input_format)?;
+            let version = changelog.schema_version.as_deref().unwrap_or("1.0");
+            if version != SCHEMA_VERSION {
+                let warning = Problem {
+                    severity: Severity::Warning,
+                    path: "schema_version".to_string(),
+                    message: format!(
+                        "written for schema version {}, run `changelog-md migrate` to upgrade to {}",
+                        version, SCHEMA_VERSION
+                    ),
+                };
+                let warning = Diagnostic::from_problem(&warning, &source, format);
+                message_format.emit(&warning.with_file(&changelog_file));
+            }
+
+            let problems = changelog.validate(
+                Some(source_dir(&changelog_file)),
+                config.tag_template.as_deref(),
+            );
+            for problem in &problems {
+                let diagnostic = Diagnostic::from_problem(problem, &source, format);
+                message_format.emit(&diagnostic.with_file(&changelog_file));
+            }
+            let errors = problems
+                .iter()
+                .filter(|p| p.severity == Severity::Error)
+                .count();
+            if errors > 0 {
+                bail!("Found {} error(s)", errors);
+            }
+            if problems.is_empty() && matches!(message_format, MessageFormat::Human) {
+                println!("No issues found");
+            }
+            Ok(())
+        }
+
+        Command::Migrate { output } => {
+            let changelog_file = changelog_file?;
+            let (source, format) = read_source(&changelog_file, input_format)?;
+            let source = format.parse_value(&source)?;
+            let output = output.unwrap_or_else(|| changelog_file.clone());
+
+            let (changelog, applied) = migrate::migrate(source)?;
+            if applied.is_empty() && output == changelog_file && !is_stdio(&output) {
+                eprintln!(
+                    "{} is already at schema version {}",
+                    changelog_file.display(),
+                    SCHEMA_VERSION
+                );
+                return Ok(());
+            }
+
+            write_output(&output, &format.to_string(&changelog)?)?;
+            eprintln!(
+                "Migrated {} ({})",
+                changelog_file.display(),
+                applied.join(", ")
+            );
+
             Ok(())
         }
 
@@ -249,7 +1101,7 @@ fn main() -> anyhow::Result<()> {
             let schema = {
                 let mut schema = schema_for!(Changelog);
                 let metadata = schema.schema.metadata.as_mut().unwrap();
-                metadata.id = Some("https://changelog-md.github.io/1.0/changelog".to_string());
+                metadata.id = Some(migrate::schema_url(SCHEMA_VERSION));
                 schema
             };
             let schema = serde_json::to_string_pretty(&schema)?;
@@ -265,67 +1117,105 @@ fn main() -> anyhow::Result<()> {
         Command::Add {
             change_type,
             description,
+            breaking,
+            track,
+            output,
         } => {
             let changelog_file = changelog_file?;
-            let format = Format::try_from(&changelog_file)?;
-
-            let mut changelog = Changelog::from_path(&changelog_file)?;
-
-            match change_type {
-                ChangeType::Added => changelog.unreleased.push_added(description),
-                ChangeType::Changed => changelog.unreleased.push_changed(description),
-                ChangeType::Deprecated => changelog.unreleased.push_deprecated(description),
-                ChangeType::Removed => changelog.unreleased.push_removed(description),
-                ChangeType::Fixed => changelog.unreleased.push_fixed(description),
-                ChangeType::Security => changelog.unreleased.push_security(description),
-            };
+            let (mut changelog, format) = load(&changelog_file, input_format)?;
+            let output = output.unwrap_or_else(|| changelog_file.clone());
+
+            let category = Category::from(change_type);
+            if config
+                .categories
+                .as_ref()
+                .is_some_and(|categories| !categories.contains(&category))
+            {
+                bail!(
+                    "{} changes are not enabled for this project",
+                    category.title()
+                );
+            }
+            let changes = changelog.unreleased.track_mut(track.as_deref())?;
+            changes.get_mut(category).push(Entry {
+                text: description,
+                breaking,
+                ..Default::default()
+            });
 
-            std::fs::write(&changelog_file, format.to_string(&changelog)?)?;
-            eprintln!("Added change to {}", &changelog_file.display());
+            write_output(&output, &format.to_string(&changelog)?)?;
+            eprintln!("Added change to {}", &output.display());
 
             Ok(())
         }
 
         Command::Release {
+            track,
             tag,
             date,
+            previous,
+            output,
+            bump,
             version,
             description,
         } => {
             let changelog_file = changelog_file?;
-            let format = Format::try_from(&changelog_file)?;
-            let mut changelog = Changelog::from_path(&changelog_file)?;
-
-            let date = date.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
-            let tag = tag.unwrap_or(version.clone());
-
-            if changelog.versions.iter().any(|v| v.version == version) {
-                bail!("Version {} already exists!", version);
-            }
-
-            let changes = changelog.unreleased;
-            changelog.unreleased = Default::default();
-            changelog.versions.insert(
-                0,
-                Version {
-                    version,
-                    tag,
-                    date,
-                    description,
-                    changes,
-                    ..Default::default()
-                },
-            );
+            let (mut changelog, format) = load(&changelog_file, input_format)?;
+            let output = output.unwrap_or_else(|| changelog_file.clone());
+
+            let date = date.unwrap_or_else(Date::today);
+            let planned = changelog
+                .next
+                .as_ref()
+                .and_then(|next| next.version.clone());
+            // The planned release is made from the next release's changes, not other tracks
+            let version = match (version, planned) {
+                (Some(version), _) => version,
+                (None, Some(planned)) if bump.is_none() && track.is_none() => planned,
+                (None, _) => {
+                    changelog.next_version(track.as_deref(), bump.map(Bump::from), date)?
+                }
+            };
+            let pending = match track {
+                Some(_) => changelog
+                    .next
+                    .take()
+                    .filter(|next| next.version.as_ref() != Some(&version)),
+                None => None,
+            };
+            let date = date.to_string();
+            let tag = match tag {
+                Some(tag) => tag,
+                None => changelog.tag_for(&version, config.tag_template.as_deref())?,
+            };
+
+            let changes = changelog.unreleased.take(track.as_deref())?;
+            changelog.add_release(Version {
+                version,
+                tag,
+                date,
+                description,
+                previous,
+                changes,
+                ..Default::default()
+            })?;
+            if pending.is_some() {
+                changelog.next = pending;
+            }
 
-            std::fs::write(&changelog_file, format.to_string(&changelog)?)?;
+            write_output(&output, &format.to_string(&changelog)?)?;
 
             Ok(())
         }
 
-        Command::Yank { version, reason } => {
+        Command::Yank {
+            version,
+            reason,
+            output,
+        } => {
             let changelog_file = changelog_file?;
-            let format = Format::try_from(&changelog_file)?;
-            let mut changelog = Changelog::from_path(&changelog_file)?;
+            let (mut changelog, format) = load(&changelog_file, input_format)?;
+            let output = output.unwrap_or_else(|| changelog_file.clone());
 
             let mut success = false;
             for released_version in &mut changelog.versions {
@@ -343,7 +1233,7 @@ fn main() -> anyhow::Result<()> {
                 bail!("Could not find version {} to yank", version);
             }
 
-            std::fs::write(&changelog_file, format.to_string(&changelog)?)?;
+            write_output(&output, &format.to_string(&changelog)?)?;
 
             Ok(())
         }
@@ -359,27 +1249,44 @@ mod test {
     use predicates::prelude::*;
     use rstest::*;
 
-    use super::Format;
-    use changelog_md::{Changelog, Changes};
+    use changelog_md::{Changelog, Changes, Format, SCHEMA_VERSION, Version};
+
+    /// A version released on 2025-01-01 and tagged with its own name
+    fn version(version: &str) -> Version {
+        Version {
+            version: version.to_string(),
+            tag: version.to_string(),
+            date: "2025-01-01".to_string(),
+            ..Default::default()
+        }
+    }
 
     fn predicate_is_yaml<Type: serde::de::DeserializeOwned>()
     -> predicates::function::FnPredicate<impl Fn(&str) -> bool, str> {
-        predicate::function(|contents: &str| serde_yml::from_str::<Type>(&contents).is_ok())
+        predicate::function(|contents: &str| serde_yml::from_str::<Type>(contents).is_ok())
     }
 
     fn predicate_is_toml<Type: serde::de::DeserializeOwned>()
     -> predicates::function::FnPredicate<impl Fn(&str) -> bool, str> {
-        predicate::function(|contents: &str| toml::from_str::<Type>(&contents).is_ok())
+        predicate::function(|contents: &str| toml::from_str::<Type>(contents).is_ok())
     }
 
     fn predicate_is_json<Type: serde::de::DeserializeOwned>()
     -> predicates::function::FnPredicate<impl Fn(&str) -> bool, str> {
-        predicate::function(|contents: &str| serde_json::from_str::<Type>(&contents).is_ok())
+        predicate::function(|contents: &str| serde_json::from_str::<Type>(contents).is_ok())
     }
 
     #[rstest]
     pub fn init_changelog(
-        #[values(Format::Yaml, Format::Toml, Format::Json)] format: Format,
+        #[values(
+            Format::Yaml,
+            Format::Toml,
+            Format::Json,
+            Format::Json5,
+            Format::Ron,
+            Format::Kdl
+        )]
+        format: Format,
     ) -> anyhow::Result<()> {
         let tempdir = assert_fs::TempDir::new()?;
 
@@ -415,18 +1322,802 @@ mod test {
         Ok(())
     }
 
-    #[rstest]
-    pub fn test_render() -> anyhow::Result<()> {
-        let mut cmd = Command::cargo_bin("changelog-md")?;
-        let tmpfile = assert_fs::NamedTempFile::new("CHANGELOG.md")?;
-
-        cmd.args(["--changelog", "CHANGELOG.yml"])
-            .arg("render")
-            .arg(tmpfile.path())
+    #[test]
+    fn test_validate_diagnostics() -> anyhow::Result<()> {
+        let source = NamedTempFile::new("CHANGELOG.toml")?;
+        source.write_str(
+            "title = \"Changelog\"\ndescription = \"\"\nrepository = \"\"\nversions = {}\n\
+             [unreleased]\nadded = [\"See `broken\"]\n",
+        )?;
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .arg("validate")
             .assert()
-            .success();
-
-        Ok(())
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "error: unreleased.added[0]: unbalanced backtick\n --> {}:6:11\n",
+                source.path().display()
+            )))
+            .stderr(predicate::str::contains("6 | added = [\"See `broken\"]"));
+
+        let output = Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["validate", "--message-format", "json"])
+            .output()?;
+        assert!(!output.status.success());
+        let messages = String::from_utf8(output.stdout)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<serde_json::Value>, _>>()?;
+        let problem = messages
+            .iter()
+            .find(|message| message["path"] == "unreleased.added[0]")
+            .expect("unbalanced backtick reported");
+        assert_eq!(
+            (&problem["line"], &problem["column"]),
+            (&6.into(), &11.into())
+        );
+
+        source.write_str(
+            "title = \"Changelog\"\ndescription = \"\"\nrepository = \"\"\nversions = {}\n\
+             [unreleased]\naddded = [\"Typo\"]\n",
+        )?;
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .arg("validate")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "error: unreleased.addded: unknown field `addded`",
+            ));
+
+        source.write_str("title = \"Changelog\"\nrepository = 1\n")?;
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .arg("render")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "--> {}:2:14",
+                source.path().display()
+            )));
+
+        // Diagnostics found while loading an archive say which archive
+        let archive = source.path().with_file_name("CHANGELOG-1.x.toml");
+        std::fs::write(&archive, "title = \"Archive\"\nrepository = 1\n")?;
+        source.write_str(
+            "title = \"Changelog\"\ndescription = \"\"\nrepository = \"\"\n\
+             include = [\"CHANGELOG-1.x.toml\"]\nversions = {}\n[unreleased]\n",
+        )?;
+        let context = format!("Failed to load archive {}:\n", archive.display());
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .arg("render")
+            .assert()
+            .failure()
+            .stderr(predicate::str::starts_with(format!("{}error: ", context)));
+
+        let output = Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["render", "--message-format", "json"])
+            .output()?;
+        let message: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        assert_eq!(
+            message["context"],
+            serde_json::json!([context.trim_end_matches(":\n")])
+        );
+        assert!(message["rendered"].as_str().unwrap().starts_with(&context));
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_render() -> anyhow::Result<()> {
+        let mut cmd = Command::cargo_bin("changelog-md")?;
+        let tmpfile = assert_fs::NamedTempFile::new("CHANGELOG.md")?;
+
+        cmd.args(["--changelog", "CHANGELOG.yml"])
+            .arg("render")
+            .arg(tmpfile.path())
+            .assert()
+            .success();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_last() -> anyhow::Result<()> {
+        let output = Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "CHANGELOG.yml"])
+            .args(["render", "--last", "1", "-"])
+            .output()?;
+        assert!(output.status.success());
+
+        let rendered = String::from_utf8(output.stdout)?;
+        let changelog = Changelog::from_path("CHANGELOG.yml")?;
+        assert!(rendered.contains(&format!("## {} -", changelog.versions[0].version)));
+        assert!(!rendered.contains(&format!("## {} -", changelog.versions[1].version)));
+        assert!(rendered.contains("See [older releases]("));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive() -> anyhow::Result<()> {
+        let dir = assert_fs::TempDir::new()?;
+        let source = dir.child("CHANGELOG.yml");
+        let tagged = |name: &str, date: &str| Version {
+            tag: format!("v{}", name),
+            date: date.to_string(),
+            ..version(name)
+        };
+        let changelog = Changelog {
+            repository: "https://github.com/example/example".to_string(),
+            versions: vec![
+                tagged("3.0.0", "2025-01-01"),
+                tagged("2.1.0", "2024-06-01"),
+                tagged("2.0.0", "2024-01-01"),
+                tagged("1.0.0", "2023-01-01"),
+            ],
+            ..Default::default()
+        };
+        source.write_str(&changelog.to_yaml()?)?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["archive", "--before", "3.0.0"])
+            .assert()
+            .success();
+
+        dir.child("CHANGELOG-2.x.yml")
+            .assert(predicate::path::exists());
+        dir.child("CHANGELOG-1.x.yml")
+            .assert(predicate::path::exists());
+
+        let source_changelog = Changelog::from_yaml(&std::fs::read_to_string(source.path())?)?;
+        assert_eq!(source_changelog.versions.len(), 1);
+        assert_eq!(
+            source_changelog.include,
+            ["CHANGELOG-2.x.yml", "CHANGELOG-1.x.yml"]
+        );
+
+        // Archives are loaded transparently, keeping the full history
+        let loaded = Changelog::from_path(source.path())?;
+        assert_eq!(
+            loaded,
+            Changelog {
+                include: source_changelog.include.clone(),
+                archived: changelog.versions[1..].to_vec(),
+                versions: changelog.versions[..1].to_vec(),
+                ..changelog.clone()
+            }
+        );
+
+        let rendered = loaded.to_string();
+        assert!(!rendered.contains("## 2.1.0"));
+        assert!(
+            rendered.contains("See [older releases](https://github.com/example/example/releases).")
+        );
+        assert!(
+            rendered
+                .contains("- [3.0.0] <https://github.com/example/example/compare/v2.1.0..v3.0.0>")
+        );
+
+        // Includes that loop back are reported, rather than followed forever
+        let looped = Changelog {
+            include: vec!["CHANGELOG.yml".to_string()],
+            ..changelog.clone()
+        };
+        source.write_str(&looped.to_yaml()?)?;
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .arg("render")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("CHANGELOG.yml includes itself"));
+        assert!(Changelog::from_path(source.path()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_by_scheme() -> anyhow::Result<()> {
+        let dir = assert_fs::TempDir::new()?;
+        let source = dir.child("CHANGELOG.yml");
+        for (scheme, versions, archive) in [
+            (
+                changelog_md::Scheme::Calver,
+                ["2025.03.1", "2024.11.2"],
+                "CHANGELOG-2024.x.yml",
+            ),
+            (
+                changelog_md::Scheme::Pep440,
+                ["2.0.0", "1!1.0.0.post1"],
+                "CHANGELOG-1.x.yml",
+            ),
+        ] {
+            let changelog = Changelog {
+                version_scheme: Some(scheme),
+                versions: versions.map(version).to_vec(),
+                ..Default::default()
+            };
+            source.write_str(&changelog.to_yaml()?)?;
+
+            Command::cargo_bin("changelog-md")?
+                .arg("--changelog")
+                .arg(source.path())
+                .args(["archive", "--keep", "1"])
+                .assert()
+                .success();
+            dir.child(archive).assert(predicate::path::exists());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_advisories() -> anyhow::Result<()> {
+        let source = NamedTempFile::new("CHANGELOG.yml")?;
+        source.write_str(
+            r#"title: Changelog
+description: ""
+repository: https://github.com/example/widget
+unreleased: {}
+versions:
+  "1.2.3":
+    tag: v1.2.3
+    date: 2025-03-01
+    security:
+      - text: Path traversal in `render`
+        advisory:
+          id: RUSTSEC-2025-0001
+          aliases: [CVE-2025-1234]
+"#,
+        )?;
+        let dir = assert_fs::TempDir::new()?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["advisories", "--format", "rustsec"])
+            .arg(dir.path())
+            .assert()
+            .success();
+        dir.child("RUSTSEC-2025-0001.toml")
+            .assert(predicate::str::contains("package = \"widget\""))
+            .assert(predicate::str::contains("patched = [\">= 1.2.3\"]"));
+
+        let output = Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["advisories", "-"])
+            .output()?;
+        assert!(output.status.success());
+        let osv: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        assert_eq!(osv[0]["id"], "RUSTSEC-2025-0001");
+        assert_eq!(osv[0]["aliases"][0], "CVE-2025-1234");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_breaking() -> anyhow::Result<()> {
+        let source = NamedTempFile::new("CHANGELOG.yml")?;
+        source.write_str(
+            r#"title: Changelog
+description: ""
+repository: https://github.com/example/widget
+unreleased: {}
+versions:
+  "2.0.0":
+    tag: v2.0.0
+    date: 2025-03-01
+    migration: Rename `render.toml` to `config.toml`.
+    changed:
+      - Faster rendering
+      - text: Renamed the configuration file
+        breaking: true
+  "1.0.0":
+    tag: v1.0.0
+    date: 2024-01-01
+    removed:
+      - text: Removed the old parser
+        breaking: true
+"#,
+        )?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["add", "removed", "--breaking", "Dropped Rust 1.70 support"])
+            .assert()
+            .success();
+
+        let changelog = Changelog::from_path(source.path())?;
+        assert!(changelog.unreleased.changes.removed[0].breaking);
+        assert!(changelog.to_string().contains(
+            "## 2.0.0 - 2025-03-01\n\n\n### ⚠ Breaking changes\n\n\
+             - Renamed the configuration file\n\n\
+             Rename `render.toml` to `config.toml`.\n"
+        ));
+
+        let output = Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["breaking", "--since", "1.0.0"])
+            .output()?;
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout)?,
+            "# Breaking changes since 1.0.0\n\
+             \n\
+             ## [Unreleased]\n\
+             \n\
+             - Dropped Rust 1.70 support\n\
+             \n\
+             ## 2.0.0 - 2025-03-01\n\
+             \n\
+             - Renamed the configuration file\n\
+             \n\
+             Rename `render.toml` to `config.toml`.\n\
+             \n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_autolink() -> anyhow::Result<()> {
+        let source = NamedTempFile::new("CHANGELOG.yml")?;
+        let changelog = Changelog {
+            repository: "https://gitlab.com/example/widget".to_string(),
+            unreleased: Changes {
+                fixed: vec!["Crash on empty input (#42), reported by @alice in `#1`".into()],
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        };
+        source.write_str(&changelog.to_yaml()?)?;
+
+        let output = Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["render", "--autolink", "-"])
+            .output()?;
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout)?.contains(
+            "- Crash on empty input ([#42](https://gitlab.com/example/widget/-/issues/42)), \
+             reported by [@alice](https://gitlab.com/alice) in `#1`\n"
+        ));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_nested_entries(
+        #[values("markdown", "atom", "rss")] format: &str,
+    ) -> anyhow::Result<()> {
+        let source = NamedTempFile::new("CHANGELOG.yml")?;
+        source.write_str(
+            r#"title: Changelog
+description: ""
+repository: https://github.com/example/widget
+unreleased: {}
+versions:
+  "1.0.0":
+    tag: v1.0.0
+    date: 2025-01-01
+    added:
+      - |
+        Configuration files, for example:
+
+        ```toml
+        output = "CHANGELOG.md"
+        ```
+      - text: New commands
+        children:
+          - "`archive`"
+          - text: "`breaking`"
+            children: ["with `--since`"]
+"#,
+        )?;
+
+        let output = Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["render", "--format", format, "-"])
+            .output()?;
+        assert!(output.status.success());
+        let rendered = String::from_utf8(output.stdout)?;
+
+        if format == "markdown" {
+            assert!(rendered.contains(
+                "- Configuration files, for example:\n\
+                 \n  ```toml\n  output = \"CHANGELOG.md\"\n  ```\n\
+                 - New commands\n  - `archive`\n  - `breaking`\n    - with `--since`\n"
+            ));
+        } else {
+            assert!(rendered.contains("&lt;li&gt;&lt;code&gt;archive&lt;/code&gt;&lt;/li&gt;"));
+            assert!(
+                rendered.contains("&lt;li&gt;with &lt;code&gt;--since&lt;/code&gt;&lt;/li&gt;")
+            );
+            assert!(rendered.contains("&lt;pre&gt;&lt;code class=&quot;language-toml&quot;&gt;"));
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_render_feed(#[values("atom", "rss")] format: &str) -> anyhow::Result<()> {
+        let tmpfile = assert_fs::NamedTempFile::new("CHANGELOG.xml")?;
+
+        Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "CHANGELOG.yml"])
+            .arg("render")
+            .args(["--format", format, "--max-entries", "2"])
+            .arg(tmpfile.path())
+            .assert()
+            .success();
+
+        let (tag, date) = if format == "atom" {
+            ("<entry>", "<updated>2025-03-20T00:00:00Z</updated>")
+        } else {
+            (
+                "<item>",
+                "<pubDate>Thu, 20 Mar 2025 00:00:00 +0000</pubDate>",
+            )
+        };
+        tmpfile
+            .assert(predicate::str::contains("/releases/tag/1.1.2"))
+            .assert(predicate::str::contains(date))
+            .assert(predicate::str::contains("&lt;li&gt;"))
+            .assert(predicate::function(|contents: &str| {
+                contents.matches(tag).count() == 2
+            }));
+
+        Ok(())
+    }
+
+    /// Feeds only depend on the changelog, so they can be checked like Markdown
+    #[test]
+    fn test_render_feed_check() -> anyhow::Result<()> {
+        let source = NamedTempFile::new("CHANGELOG.yml")?;
+        let changelog = Changelog {
+            versions: vec![],
+            ..Default::default()
+        };
+        source.write_str(&changelog.to_yaml()?)?;
+        let feed = NamedTempFile::new("CHANGELOG.atom.xml")?;
+
+        for check in [false, true] {
+            Command::cargo_bin("changelog-md")?
+                .arg("--changelog")
+                .arg(source.path())
+                .args(["render", "--format", "atom"])
+                .args(check.then_some("--check"))
+                .arg(feed.path())
+                .assert()
+                .success();
+        }
+        feed.assert(predicate::str::contains("<updated>").not());
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_render_check() -> anyhow::Result<()> {
+        let tmpfile = assert_fs::NamedTempFile::new("CHANGELOG.md")?;
+        tmpfile.write_str("# Stale\n")?;
+
+        Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "CHANGELOG.yml"])
+            .args(["render", "--check"])
+            .arg(tmpfile.path())
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("-# Stale"))
+            .stdout(predicate::str::contains("+# changelog-md"));
+        tmpfile.assert("# Stale\n");
+
+        Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "CHANGELOG.yml", "render"])
+            .arg(tmpfile.path())
+            .assert()
+            .success();
+
+        Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "CHANGELOG.yml"])
+            .args(["render", "--check"])
+            .arg(tmpfile.path())
+            .assert()
+            .success();
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_render_watch() -> anyhow::Result<()> {
+        let tmpdir = assert_fs::TempDir::new()?;
+        let source = tmpdir.child("CHANGELOG.yml");
+        let output = tmpdir.child("CHANGELOG.md");
+        source.write_str(&Changelog::default().to_yaml()?)?;
+
+        let mut child = Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["render", "--watch"])
+            .stderr(std::process::Stdio::null())
+            .spawn()?;
+
+        let wait_for = |needle: &str| {
+            (0..50).any(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                std::fs::read_to_string(output.path()).is_ok_and(|s| s.contains(needle))
+            })
+        };
+
+        let initial = wait_for("Starting using");
+        // Invalid sources are reported without exiting
+        source.write_str("title: [")?;
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let mut changelog = Changelog::default();
+        changelog
+            .unreleased
+            .changes
+            .fixed
+            .push("Watched change".into());
+        source.write_str(&changelog.to_yaml()?)?;
+        let updated = wait_for("Watched change");
+
+        child.kill()?;
+        child.wait()?;
+
+        assert!(initial, "initial render was not written");
+        assert!(updated, "changes were not re-rendered");
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_merge_driver() -> anyhow::Result<()> {
+        let tmpdir = assert_fs::TempDir::new()?;
+        let base = Changelog::default();
+        let mut ours = base.clone();
+        ours.unreleased.changes.fixed.push("Our fix".into());
+        let mut theirs = base.clone();
+        theirs.unreleased.changes.fixed.push("Their fix".into());
+
+        // git passes temporary files without the original extension
+        for (name, changelog) in [("base", &base), ("ours", &ours), ("theirs", &theirs)] {
+            tmpdir.child(name).write_str(&changelog.to_toml()?)?;
+        }
+
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["merge-driver", "base", "ours", "theirs"])
+            .assert()
+            .success();
+
+        let merged = Changelog::from_toml(&std::fs::read_to_string(tmpdir.child("ours"))?)?;
+        assert_eq!(merged.unreleased.changes.fixed, ["Our fix", "Their fix"]);
+
+        // Sides that don't parse fall back to a line-based merge with conflict markers
+        for (name, contents) in [
+            ("base", "title = \"Changelog\"\n"),
+            ("ours", "title = \"Ours\"\n"),
+            ("theirs", "title = \"Theirs\"\n"),
+        ] {
+            tmpdir.child(name).write_str(contents)?;
+        }
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["merge-driver", "base", "ours", "theirs", "CHANGELOG.toml"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Failed to parse base"));
+        tmpdir
+            .child("ours")
+            .assert(predicate::str::contains("<<<<<<< ours\ntitle = \"Ours\"\n"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_install_merge_driver() -> anyhow::Result<()> {
+        let tmpdir = assert_fs::TempDir::new()?;
+        Command::new("git")
+            .current_dir(&tmpdir)
+            .args(["init", "-q"])
+            .assert()
+            .success();
+        tmpdir
+            .child("CHANGELOG.yml")
+            .write_str(&Changelog::default().to_yaml()?)?;
+
+        for _ in 0..2 {
+            Command::cargo_bin("changelog-md")?
+                .current_dir(&tmpdir)
+                .arg("install-merge-driver")
+                .assert()
+                .success();
+        }
+
+        tmpdir
+            .child(".gitattributes")
+            .assert("CHANGELOG.yml merge=changelog-md\n");
+        Command::new("git")
+            .current_dir(&tmpdir)
+            .args(["config", "merge.changelog-md.driver"])
+            .assert()
+            .success()
+            .stdout("changelog-md merge-driver %O %A %B %P\n");
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_migrate() -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
+        tmpfile.write_str(
+            "$schema: https://changelog-md.github.io/1.0/changelog
+title: Changelog
+description: ''
+repository: ''
+unreleased: {}
+versions:
+  '2.0.0':
+    tag: '2.0.0'
+    date: '2025-02-01'
+  '10.0.0':
+    tag: '10.0.0'
+    date: '2025-01-01'
+",
+        )?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .arg("validate")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("changelog-md migrate"));
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .arg("migrate")
+            .assert()
+            .success();
+
+        let changelog = Changelog::from_path(tmpfile.path())?;
+        assert_eq!(changelog.schema_version.as_deref(), Some(SCHEMA_VERSION));
+        assert_eq!(
+            changelog.schema.as_deref(),
+            Some("https://changelog-md.github.io/1.1/changelog")
+        );
+        // Migrating must not reorder versions
+        assert_eq!(changelog.versions[0].version, "2.0.0");
+
+        Ok(())
+    }
+
+    /// Sources with unknown extensions are detected from their contents
+    #[rstest]
+    pub fn test_detect_format(
+        #[values(Format::Json5, Format::Ron, Format::Kdl, Format::Toml)] format: Format,
+    ) -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.txt")?;
+        tmpfile.write_str(&format.to_string(&Changelog::default())?)?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .args(["add", "fixed", "detected"])
+            .assert()
+            .success();
+
+        let contents = std::fs::read_to_string(tmpfile.path())?;
+        assert_eq!(
+            format.parse(&contents)?.unreleased.changes.fixed,
+            ["detected"]
+        );
+
+        Ok(())
+    }
+
+    /// Sources detected from their contents report errors in that format
+    #[test]
+    fn test_detect_format_errors() -> anyhow::Result<()> {
+        assert_cmd::Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "-", "validate"])
+            .write_stdin("title: Changelog\ndescription: ''\nrepository: ''\nunreleased: {}\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("missing field `versions`"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_json5_comments() -> anyhow::Result<()> {
+        let changelog = Changelog::from_json5(
+            r#"{
+                // Comments and trailing commas are allowed
+                title: "Changelog",
+                description: "",
+                repository: "",
+                unreleased: { added: ["Something",], },
+                versions: {},
+            }"#,
+        )?;
+        assert_eq!(changelog.unreleased.changes.added, ["Something"]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    pub fn test_stdio() -> anyhow::Result<()> {
+        let toml = Changelog::default().to_toml()?;
+
+        // Pipe a source through a mutating command and a conversion
+        let added = assert_cmd::Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "-", "--input-format", "toml"])
+            .args(["add", "fixed", "piped"])
+            .write_stdin(toml)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        let converted = assert_cmd::Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "-", "--input-format", "toml"])
+            .args(["convert", "--format", "json", "--output", "-"])
+            .write_stdin(added)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let changelog = Changelog::from_json(std::str::from_utf8(&converted)?)?;
+        assert_eq!(changelog.unreleased.changes.fixed, ["piped"]);
+
+        assert_cmd::Command::cargo_bin("changelog-md")?
+            .args(["--changelog", "-", "render", "-"])
+            .write_stdin(converted)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("- piped"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_output_option() -> anyhow::Result<()> {
+        let source = NamedTempFile::new("CHANGELOG.yml")?;
+        let output = NamedTempFile::new("RELEASED.yml")?;
+        source.write_str(&Changelog::default().to_yaml()?)?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(source.path())
+            .args(["release", "1.0.0", "--output"])
+            .arg(output.path())
+            .assert()
+            .success();
+
+        assert!(Changelog::from_path(source.path())?.versions.is_empty());
+        assert_eq!(Changelog::from_path(output.path())?.versions.len(), 1);
+
+        Ok(())
     }
 
     #[rstest]
@@ -480,7 +2171,7 @@ mod test {
 
         Command::cargo_bin("changelog-md")?
             .arg("schema")
-            .arg(&tmpfile.path())
+            .arg(tmpfile.path())
             .assert()
             .success();
 
@@ -497,7 +2188,7 @@ mod test {
 
         Command::cargo_bin("changelog-md")?
             .arg("--changelog")
-            .arg(&tmpfile.path())
+            .arg(tmpfile.path())
             .arg("init")
             .assert()
             .success();
@@ -506,7 +2197,7 @@ mod test {
 
         Command::cargo_bin("changelog-md")?
             .arg("--changelog")
-            .arg(&tmpfile.path())
+            .arg(tmpfile.path())
             .arg("add")
             .arg("changed")
             .arg("testing adding a new change")
@@ -520,21 +2211,137 @@ mod test {
 
                 changelog
                     .unreleased
+                    .changes
                     .changed
-                    .contains(&"testing adding a new change".to_string())
+                    .contains(&"testing adding a new change".into())
             }));
 
         Ok(())
     }
 
+    #[rstest]
+    fn test_config_file() -> anyhow::Result<()> {
+        let tmpdir = assert_fs::TempDir::new()?;
+        tmpdir.child(".git").create_dir_all()?;
+        tmpdir.child("docs").create_dir_all()?;
+        tmpdir.child(".changelog-md.toml").write_str(
+            r#"
+changelog = "docs/CHANGELOG.yml"
+output = "docs/CHANGES.md"
+tag-template = "v{version}"
+categories = ["added", "changed", "fixed"]
+
+[render]
+revisions = false
+"#,
+        )?;
+
+        for args in [
+            vec!["init"],
+            vec!["add", "fixed", "a fix"],
+            vec!["release", "1.0.0"],
+            vec!["render"],
+        ] {
+            Command::cargo_bin("changelog-md")?
+                .current_dir(&tmpdir)
+                .args(args)
+                .assert()
+                .success();
+        }
+
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["add", "security", "not enabled"])
+            .assert()
+            .failure();
+
+        let changelog = Changelog::from_path(tmpdir.child("docs/CHANGELOG.yml").path())?;
+        assert_eq!(changelog.versions[0].tag, "v1.0.0");
+
+        // The changelog's own template takes precedence over the configured one
+        let changelog = Changelog {
+            component: Some("core".to_string()),
+            tag_template: Some("{component}-v{version}".to_string()),
+            ..changelog
+        };
+        tmpdir
+            .child("docs/CHANGELOG.yml")
+            .write_str(&changelog.to_yaml()?)?;
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["release", "1.1.0"])
+            .assert()
+            .success();
+        let changelog = Changelog::from_path(tmpdir.child("docs/CHANGELOG.yml").path())?;
+        assert_eq!(changelog.versions[0].tag, "core-v1.1.0");
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .arg("validate")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "warning: versions.1.0.0.tag: tag v1.0.0 does not match the template",
+            ));
+
+        // Placeholders that can't be filled in fail the release, rather than being written out
+        let changelog = Changelog {
+            component: None,
+            ..changelog
+        };
+        tmpdir
+            .child("docs/CHANGELOG.yml")
+            .write_str(&changelog.to_yaml()?)?;
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["release", "1.2.0"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("but `component` is not set"));
+
+        tmpdir
+            .child("docs/CHANGES.md")
+            .assert(predicate::str::contains("- a fix"))
+            .assert(predicate::str::contains("# Revisions").not());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_cargo_metadata_config() -> anyhow::Result<()> {
+        let tmpdir = assert_fs::TempDir::new()?;
+        tmpdir.child(".git").create_dir_all()?;
+        tmpdir.child("Cargo.toml").write_str(
+            r#"
+[package]
+name = "example"
+
+[package.metadata.changelog-md]
+changelog = "HISTORY.toml"
+"#,
+        )?;
+
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["init", "--format", "toml"])
+            .assert()
+            .success();
+
+        tmpdir
+            .child("HISTORY.toml")
+            .assert(predicate_is_toml::<Changelog>());
+
+        Ok(())
+    }
+
     #[rstest]
     fn test_release() -> anyhow::Result<()> {
         let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
         let changelog = Changelog {
             unreleased: Changes {
-                changed: vec!["Testing releases".to_string()],
+                changed: vec!["Testing releases".into()],
                 ..Default::default()
-            },
+            }
+            .into(),
             versions: vec![],
             ..Default::default()
         };
@@ -542,7 +2349,15 @@ mod test {
 
         Command::cargo_bin("changelog-md")?
             .arg("--changelog")
-            .arg(&tmpfile.path())
+            .arg(tmpfile.path())
+            .args(["release", "--date", "2025-02-30", "1.2.3"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("there is no such day"));
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
             .arg("release")
             .args(["--tag", "v1.2.3"])
             .args(["--date", "2025-01-01"])
@@ -552,10 +2367,10 @@ mod test {
             .success();
 
         tmpfile.assert(predicate::function(|contents: &str| {
-            let changelog = Changelog::from_yaml(&contents).expect("Failed to parse");
+            let changelog = Changelog::from_yaml(contents).expect("Failed to parse");
             let version = changelog.versions.first().expect("Did not find a version");
 
-            changelog.unreleased.changed.is_empty()
+            changelog.unreleased.changes.changed.is_empty()
                 && changelog.versions.len() == 1
                 && version.version == "1.2.3"
                 && version.tag == "v1.2.3"
@@ -563,11 +2378,363 @@ mod test {
                 && version.description == Some("some description".to_string())
                 && version.changes
                     == Changes {
-                        changed: vec!["Testing releases".to_string()],
+                        changed: vec!["Testing releases".into()],
                         ..Default::default()
                     }
         }));
 
         Ok(())
     }
+
+    #[test]
+    fn test_release_maintenance_line() -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
+        let tagged = |name: &str| Version {
+            tag: format!("v{}", name),
+            ..version(name)
+        };
+        let changelog = Changelog {
+            repository: "https://github.com/example/example".to_string(),
+            unreleased: Changes {
+                fixed: vec!["Backported fix".into()],
+                ..Default::default()
+            }
+            .into(),
+            versions: vec![tagged("2.0.0"), tagged("1.4.2")],
+            ..Default::default()
+        };
+        tmpfile.write_str(&changelog.to_yaml()?)?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .arg("release")
+            .args(["--tag", "v1.4.3"])
+            .arg("1.4.3")
+            .assert()
+            .success();
+
+        let changelog = Changelog::from_path(tmpfile.path())?;
+        let versions = changelog
+            .versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(versions, ["2.0.0", "1.4.3", "1.4.2"]);
+        assert_eq!(changelog.versions[0].previous.as_deref(), Some("1.4.2"));
+
+        let markdown = changelog.to_string();
+        assert!(
+            markdown
+                .contains("- [2.0.0] <https://github.com/example/example/compare/v1.4.2..v2.0.0>")
+        );
+        assert!(
+            markdown
+                .contains("- [1.4.3] <https://github.com/example/example/compare/v1.4.2..v1.4.3>")
+        );
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .arg("release")
+            .args(["--previous", "1.3.0"])
+            .arg("1.3.1")
+            .assert()
+            .failure();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_split() -> anyhow::Result<()> {
+        let tmpdir = assert_fs::TempDir::new()?;
+        tmpdir.child(".git").create_dir_all()?;
+        tmpdir.child(".changelog-md.toml").write_str(
+            r#"
+[split]
+front-matter = "toml"
+index = "_index.md"
+tags = ["release"]
+"#,
+        )?;
+        let changelog = Changelog {
+            versions: vec![Version {
+                version: "1.0.0-rc.1".to_string(),
+                tag: "v1.0.0-rc.1".to_string(),
+                date: "2025-01-01".to_string(),
+                changes: Changes {
+                    added: vec!["Everything".into()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        tmpdir
+            .child("CHANGELOG.yml")
+            .write_str(&changelog.to_yaml()?)?;
+
+        Command::cargo_bin("changelog-md")?
+            .current_dir(&tmpdir)
+            .args(["render", "--split", "docs"])
+            .assert()
+            .success();
+
+        tmpdir
+            .child("docs/_index.md")
+            .assert(predicate::str::contains(
+                "- [1.0.0-rc.1](1-0-0-rc-1.md) - 2025-01-01\n",
+            ));
+        tmpdir
+            .child("docs/unreleased.md")
+            .assert(predicate::str::contains("### Added\n"));
+        tmpdir.child("docs/1-0-0-rc-1.md").assert(
+            "+++\n\
+             title = \"1.0.0-rc.1\"\n\
+             date = 2025-01-01\n\
+             weight = 2\n\
+             tags = [\"release\"]\n\
+             +++\n\
+             \n\
+             ### Added\n\
+             \n\
+             - Everything\n\
+             \n",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_between() -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
+        let adding = |name: &str, added: &str| Version {
+            changes: Changes {
+                added: vec![added.into()],
+                ..Default::default()
+            },
+            ..version(name)
+        };
+        let changelog = Changelog {
+            versions: vec![
+                adding("2.5.0", "Exports"),
+                adding("2.0.0", "Imports"),
+                adding("1.2.0", "Basics"),
+            ],
+            ..Default::default()
+        };
+        tmpfile.write_str(&changelog.to_yaml()?)?;
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .args(["between", "1.2.0", "2.5.0"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "## Added\n\n- Exports (2.5.0)\n- Imports (2.0.0)\n",
+            ));
+
+        let output = Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .args(["between", "2.0.0", "2.5.0", "--format", "json"])
+            .output()?;
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        assert_eq!(json["changes"]["added"][0]["text"], "Exports");
+        assert_eq!(json["changes"]["added"].as_array().map(Vec::len), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_release_track() -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
+        let changelog = Changelog {
+            versions: vec![Version {
+                version: "1.4.0".to_string(),
+                tag: "1.4.0".to_string(),
+                date: "2025-01-01".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        tmpfile.write_str(&changelog.to_yaml()?)?;
+
+        for args in [
+            vec!["add", "fixed", "A fix"],
+            vec![
+                "add",
+                "removed",
+                "Old API",
+                "--breaking",
+                "--track",
+                "next-major",
+            ],
+        ] {
+            Command::cargo_bin("changelog-md")?
+                .arg("--changelog")
+                .arg(tmpfile.path())
+                .args(args)
+                .assert()
+                .success();
+        }
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .args(["add", "fixed", "A fix", "--track", "fixed"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Unreleased tracks can't be named after the fixed category",
+            ));
+        let changelog = Changelog::from_path(tmpfile.path())?;
+        assert_eq!(
+            changelog.unreleased.tracks["next-major"].removed[0].text,
+            "Old API"
+        );
+        assert!(
+            changelog
+                .to_string()
+                .contains("## [Unreleased] (next-major)\n\n### ⚠ Breaking changes\n")
+        );
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .args(["release", "--track", "next-major", "--date", "2025-02-01"])
+            .assert()
+            .success();
+        let changelog = Changelog::from_path(tmpfile.path())?;
+        assert_eq!(changelog.versions[0].version, "2.0.0");
+        assert_eq!(changelog.versions[0].changes.removed[0].text, "Old API");
+        assert!(changelog.unreleased.tracks.is_empty());
+        assert_eq!(changelog.unreleased.changes.fixed, ["A fix"]);
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .args(["release", "--track", "next-major"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Unreleased track next-major does not exist",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_release_planned_version() -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
+        let changelog = Changelog {
+            next: Some(changelog_md::Next {
+                version: Some("2.0.0".to_string()),
+                target: Some("2025-06".to_string()),
+                theme: Some("Faster rendering".to_string()),
+            }),
+            unreleased: Changes {
+                fixed: vec!["A fix".into()],
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        };
+        tmpfile.write_str(&changelog.to_yaml()?)?;
+        assert!(changelog.to_string().contains(
+            "## [Unreleased] - planned 2.0.0 (target 2025-06)\n\nFaster rendering\n\n### Fixed\n"
+        ));
+
+        Command::cargo_bin("changelog-md")?
+            .arg("--changelog")
+            .arg(tmpfile.path())
+            .args(["release", "--date", "2025-06-02"])
+            .assert()
+            .success();
+
+        let changelog = Changelog::from_path(tmpfile.path())?;
+        assert_eq!(changelog.versions[0].version, "2.0.0");
+        assert_eq!(changelog.next, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_release_maintenance_keeps_next() -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
+        let next = changelog_md::Next {
+            version: None,
+            target: Some("2025-06".to_string()),
+            theme: Some("Faster rendering".to_string()),
+        };
+        let changelog = Changelog {
+            next: Some(next.clone()),
+            unreleased: Changes {
+                fixed: vec!["A fix".into()],
+                ..Default::default()
+            }
+            .into(),
+            versions: vec![version("2.0.0"), version("1.4.2")],
+            ..Default::default()
+        };
+        tmpfile.write_str(&changelog.to_yaml()?)?;
+
+        let release = |version: &str| -> anyhow::Result<Changelog> {
+            Command::cargo_bin("changelog-md")?
+                .arg("--changelog")
+                .arg(tmpfile.path())
+                .args(["release", "--date", "2025-06-02", version])
+                .assert()
+                .success();
+            Changelog::from_path(tmpfile.path())
+        };
+
+        // A maintenance release doesn't deliver the plan
+        assert_eq!(release("1.4.3")?.next, Some(next));
+        // A new latest version does
+        assert_eq!(release("2.1.0")?.next, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_release_next_version() -> anyhow::Result<()> {
+        let tmpfile = NamedTempFile::new("CHANGELOG.yml")?;
+        let release = |args: &[&str]| -> anyhow::Result<String> {
+            Command::cargo_bin("changelog-md")?
+                .arg("--changelog")
+                .arg(tmpfile.path())
+                .arg("release")
+                .args(args)
+                .assert()
+                .success();
+            let changelog = Changelog::from_path(tmpfile.path())?;
+            Ok(changelog.versions[0].version.clone())
+        };
+
+        let changelog = Changelog {
+            unreleased: Changes {
+                added: vec!["A feature".into()],
+                ..Default::default()
+            }
+            .into(),
+            versions: vec![version("1.4.2")],
+            ..Default::default()
+        };
+        tmpfile.write_str(&changelog.to_yaml()?)?;
+        assert_eq!(release(&[])?, "1.5.0");
+        assert_eq!(release(&["--bump", "major"])?, "2.0.0");
+
+        let changelog = Changelog {
+            version_scheme: Some(changelog_md::Scheme::Calver),
+            versions: vec![version("2025.03.01")],
+            ..changelog
+        };
+        tmpfile.write_str(&changelog.to_yaml()?)?;
+        assert_eq!(release(&["--date", "2025-03-01"])?, "2025.03.01.1");
+        assert_eq!(release(&["--date", "2025-04-02"])?, "2025.04.02");
+
+        Ok(())
+    }
 }
@@ -0,0 +1,536 @@
+//! Structural three-way merging of changelogs
+//!
+//! Used by `changelog-md merge-driver` so that branches which each add
+//! entries or release versions can be merged without textual conflicts.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Category, Changelog, Changes, Entry, Unreleased, Version};
+
+/// A change made on both sides of a merge that cannot be reconciled
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// Path to the conflicting field, e.g. `versions.1.2.0.date`
+    pub path: String,
+    /// Human readable description of the clash
+    pub message: String,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Merge `ours` and `theirs`, both derived from `base`
+///
+/// Entries are unioned per category, and new versions from both sides are kept
+/// unless they clash. Any conflicting changes are returned instead of a result.
+pub fn merge(
+    base: &Changelog,
+    ours: &Changelog,
+    theirs: &Changelog,
+) -> Result<Changelog, Vec<Conflict>> {
+    let mut conflicts = vec![];
+
+    let merged = Changelog {
+        schema: merge_value(
+            "$schema",
+            &base.schema,
+            &ours.schema,
+            &theirs.schema,
+            &mut conflicts,
+        ),
+        schema_version: merge_value(
+            "schema_version",
+            &base.schema_version,
+            &ours.schema_version,
+            &theirs.schema_version,
+            &mut conflicts,
+        ),
+        version_scheme: merge_value(
+            "version_scheme",
+            &base.version_scheme,
+            &ours.version_scheme,
+            &theirs.version_scheme,
+            &mut conflicts,
+        ),
+        title: merge_value(
+            "title",
+            &base.title,
+            &ours.title,
+            &theirs.title,
+            &mut conflicts,
+        ),
+        description: merge_value(
+            "description",
+            &base.description,
+            &ours.description,
+            &theirs.description,
+            &mut conflicts,
+        ),
+        repository: merge_value(
+            "repository",
+            &base.repository,
+            &ours.repository,
+            &theirs.repository,
+            &mut conflicts,
+        ),
+        component: merge_value(
+            "component",
+            &base.component,
+            &ours.component,
+            &theirs.component,
+            &mut conflicts,
+        ),
+        tag_template: merge_value(
+            "tag_template",
+            &base.tag_template,
+            &ours.tag_template,
+            &theirs.tag_template,
+            &mut conflicts,
+        ),
+        include: merge_value(
+            "include",
+            &base.include,
+            &ours.include,
+            &theirs.include,
+            &mut conflicts,
+        ),
+        next: merge_value("next", &base.next, &ours.next, &theirs.next, &mut conflicts),
+        unreleased: merge_unreleased(&base.unreleased, &ours.unreleased, &theirs.unreleased),
+        versions: merge_versions(
+            &base.versions,
+            &ours.versions,
+            &theirs.versions,
+            &mut conflicts,
+        ),
+        archived: ours.archived.clone(),
+    };
+    released_twice(base, ours, theirs, &mut conflicts);
+
+    if conflicts.is_empty() {
+        Ok(merged)
+    } else {
+        Err(conflicts)
+    }
+}
+
+fn merge_value<T: PartialEq + Clone + std::fmt::Debug>(
+    path: &str,
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    conflicts: &mut Vec<Conflict>,
+) -> T {
+    if ours == theirs || theirs == base {
+        ours.clone()
+    } else if ours == base {
+        theirs.clone()
+    } else {
+        conflicts.push(Conflict {
+            path: path.to_string(),
+            message: format!("changed to {:?} and {:?}", ours, theirs),
+        });
+        ours.clone()
+    }
+}
+
+fn merge_changes(base: &Changes, ours: &Changes, theirs: &Changes) -> Changes {
+    let mut merged = Changes::default();
+    for category in Category::ALL {
+        *merged.get_mut(category) =
+            merge_entries(base.get(category), ours.get(category), theirs.get(category));
+    }
+    merged
+}
+
+/// Merge each unreleased track, dropping tracks released on one side and left alone on the other
+fn merge_unreleased(base: &Unreleased, ours: &Unreleased, theirs: &Unreleased) -> Unreleased {
+    let empty = Changes::default();
+    let names = ours
+        .tracks
+        .keys()
+        .chain(theirs.tracks.keys())
+        .collect::<BTreeSet<_>>();
+    let mut tracks = BTreeMap::new();
+    for name in names {
+        let [base_track, our_track, their_track] =
+            [base, ours, theirs].map(|side| side.tracks.get(name).unwrap_or(&empty));
+        let merged = merge_changes(base_track, our_track, their_track);
+        let kept = ours.tracks.contains_key(name) && theirs.tracks.contains_key(name);
+        if kept || !merged.is_empty() {
+            tracks.insert(name.clone(), merged);
+        }
+    }
+
+    Unreleased {
+        changes: merge_changes(&base.changes, &ours.changes, &theirs.changes),
+        tracks,
+    }
+}
+
+/// Union two lists of entries, honouring removals made on either side
+fn merge_entries(base: &[Entry], ours: &[Entry], theirs: &[Entry]) -> Vec<Entry> {
+    let mut merged = ours
+        .iter()
+        .filter(|entry| !base.contains(entry) || theirs.contains(entry))
+        .cloned()
+        .collect::<Vec<_>>();
+    for entry in theirs {
+        if !base.contains(entry) && !merged.contains(entry) {
+            merged.push(entry.clone());
+        }
+    }
+    merged
+}
+
+/// Report new versions on both sides that release the same unreleased entries from `base`
+fn released_twice(
+    base: &Changelog,
+    ours: &Changelog,
+    theirs: &Changelog,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let new_versions = |side: &Changelog, other: &Changelog| {
+        side.versions
+            .iter()
+            .filter(|v| find(&base.versions, &v.version).is_none())
+            .filter(|v| find(&other.versions, &v.version).is_none())
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let their_versions = new_versions(theirs, ours);
+    for our_version in new_versions(ours, theirs) {
+        for their_version in &their_versions {
+            let shared = Category::ALL.into_iter().find_map(|category| {
+                our_version.changes.get(category).iter().find(|entry| {
+                    their_version.changes.get(category).contains(entry)
+                        && base
+                            .unreleased
+                            .all_tracks()
+                            .any(|(_, changes)| changes.get(category).contains(entry))
+                })
+            });
+            if let Some(entry) = shared {
+                conflicts.push(Conflict {
+                    path: format!("versions.{}", our_version.version),
+                    message: format!(
+                        "releases {:?} from unreleased, as does version {} on the other side",
+                        entry.text, their_version.version
+                    ),
+                });
+            }
+        }
+    }
+}
+
+fn find<'a>(versions: &'a [Version], version: &str) -> Option<&'a Version> {
+    versions.iter().find(|v| v.version == version)
+}
+
+fn merge_versions(
+    base: &[Version],
+    ours: &[Version],
+    theirs: &[Version],
+    conflicts: &mut Vec<Conflict>,
+) -> Vec<Version> {
+    let mut merged = vec![];
+
+    for our_version in ours {
+        let path = format!("versions.{}", our_version.version);
+        match (
+            find(base, &our_version.version),
+            find(theirs, &our_version.version),
+        ) {
+            // Unchanged on their side, or changed identically
+            (_, Some(their_version)) if their_version == our_version => {
+                merged.push(our_version.clone())
+            }
+            (Some(base_version), Some(their_version)) => merged.push(merge_version(
+                &path,
+                base_version,
+                our_version,
+                their_version,
+                conflicts,
+            )),
+            // Removed on their side
+            (Some(base_version), None) => {
+                if base_version != our_version {
+                    conflicts.push(Conflict {
+                        path,
+                        message: "modified on one side and removed on the other".to_string(),
+                    });
+                    merged.push(our_version.clone());
+                }
+            }
+            // Only released on our side
+            (None, None) => merged.push(our_version.clone()),
+            // Released on both sides with different contents
+            (None, Some(_)) => {
+                conflicts.push(Conflict {
+                    path,
+                    message: "released on both sides with different contents".to_string(),
+                });
+                merged.push(our_version.clone());
+            }
+        }
+    }
+
+    // Insert their new versions before the version that follows them on their side
+    for (idx, their_version) in theirs.iter().enumerate() {
+        if find(ours, &their_version.version).is_some() {
+            continue;
+        }
+        match find(base, &their_version.version) {
+            Some(base_version) => {
+                // Removed on our side
+                if base_version != their_version {
+                    conflicts.push(Conflict {
+                        path: format!("versions.{}", their_version.version),
+                        message: "modified on one side and removed on the other".to_string(),
+                    });
+                } else {
+                    continue;
+                }
+            }
+            None => {
+                if let Some(clash) = merged.iter().find(|v| v.tag == their_version.tag) {
+                    conflicts.push(Conflict {
+                        path: format!("versions.{}.tag", their_version.version),
+                        message: format!(
+                            "tag {} is already used by version {}",
+                            their_version.tag, clash.version
+                        ),
+                    });
+                }
+            }
+        }
+
+        let position = theirs[idx + 1..]
+            .iter()
+            .find_map(|next| merged.iter().position(|v| v.version == next.version))
+            .unwrap_or(merged.len());
+        if position > 0 && position < merged.len() {
+            // The version above used to follow the one below by position, keep it that way
+            // unless it is also directly above this version on their side
+            let above_on_their_side = idx
+                .checked_sub(1)
+                .is_some_and(|above| theirs[above].version == merged[position - 1].version);
+            if !above_on_their_side {
+                let below = merged[position].version.clone();
+                merged[position - 1].previous.get_or_insert(below);
+            }
+        }
+        merged.insert(position, their_version.clone());
+    }
+
+    merged
+}
+
+fn merge_version(
+    path: &str,
+    base: &Version,
+    ours: &Version,
+    theirs: &Version,
+    conflicts: &mut Vec<Conflict>,
+) -> Version {
+    Version {
+        version: ours.version.clone(),
+        tag: merge_value(
+            &format!("{}.tag", path),
+            &base.tag,
+            &ours.tag,
+            &theirs.tag,
+            conflicts,
+        ),
+        date: merge_value(
+            &format!("{}.date", path),
+            &base.date,
+            &ours.date,
+            &theirs.date,
+            conflicts,
+        ),
+        description: merge_value(
+            &format!("{}.description", path),
+            &base.description,
+            &ours.description,
+            &theirs.description,
+            conflicts,
+        ),
+        yanked: merge_value(
+            &format!("{}.yanked", path),
+            &base.yanked,
+            &ours.yanked,
+            &theirs.yanked,
+            conflicts,
+        ),
+        previous: merge_value(
+            &format!("{}.previous", path),
+            &base.previous,
+            &ours.previous,
+            &theirs.previous,
+            conflicts,
+        ),
+        migration: merge_value(
+            &format!("{}.migration", path),
+            &base.migration,
+            &ours.migration,
+            &theirs.migration,
+            conflicts,
+        ),
+        changes: merge_changes(&base.changes, &ours.changes, &theirs.changes),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn changelog(unreleased: &[&str], versions: Vec<Version>) -> Changelog {
+        Changelog {
+            unreleased: Changes {
+                fixed: unreleased.iter().map(|s| Entry::from(*s)).collect(),
+                ..Default::default()
+            }
+            .into(),
+            versions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unions_unreleased_entries() {
+        let base = changelog(&["a"], vec![]);
+        let ours = changelog(&["a", "b"], vec![]);
+        let theirs = changelog(&["a", "c"], vec![]);
+
+        let merged = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(merged.unreleased.changes.fixed, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn release_on_one_side_keeps_new_entries() {
+        let base = changelog(&["a"], vec![Version::fixture("1.0.0", &[])]);
+        let ours = changelog(
+            &[],
+            vec![
+                Version::fixture("1.1.0", &["a"]),
+                Version::fixture("1.0.0", &[]),
+            ],
+        );
+        let theirs = changelog(&["a", "b"], vec![Version::fixture("1.0.0", &[])]);
+
+        let merged = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(merged.unreleased.changes.fixed, ["b"]);
+        assert_eq!(merged.versions.len(), 2);
+        assert_eq!(merged.versions[0].changes.fixed, ["a"]);
+    }
+
+    #[test]
+    fn merges_unreleased_tracks() {
+        let track = |fixed: &[&str]| Changes {
+            fixed: fixed.iter().map(|s| Entry::from(*s)).collect(),
+            ..Default::default()
+        };
+        let mut base = changelog(&[], vec![]);
+        base.unreleased
+            .tracks
+            .insert("next-major".into(), track(&["a"]));
+        let mut ours = base.clone();
+        ours.unreleased
+            .tracks
+            .insert("next-major".into(), track(&["a", "b"]));
+        let mut theirs = base.clone();
+        theirs.unreleased.tracks.clear();
+        theirs
+            .unreleased
+            .tracks
+            .insert("next-minor".into(), track(&["c"]));
+
+        let merged = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(
+            merged.unreleased.tracks,
+            BTreeMap::from([
+                ("next-major".to_string(), track(&["b"])),
+                ("next-minor".to_string(), track(&["c"])),
+            ])
+        );
+
+        // A track released on one side and unchanged on the other is gone
+        ours.unreleased
+            .tracks
+            .insert("next-major".into(), track(&["a"]));
+        let merged = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(merged.unreleased.tracks.len(), 1);
+    }
+
+    #[test]
+    fn new_versions_from_both_sides() {
+        let base = changelog(&[], vec![Version::fixture("1.0.0", &[])]);
+        let ours = changelog(
+            &[],
+            vec![
+                Version::fixture("1.1.0", &["a"]),
+                Version::fixture("1.0.0", &[]),
+            ],
+        );
+        let theirs = changelog(
+            &[],
+            vec![
+                Version::fixture("1.0.1", &["b"]),
+                Version::fixture("1.0.0", &[]),
+            ],
+        );
+
+        let merged = merge(&base, &ours, &theirs).unwrap();
+        let versions = merged
+            .versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(versions, ["1.1.0", "1.0.1", "1.0.0"]);
+        // 1.1.0 still follows 1.0.0, not the maintenance release below it
+        assert_eq!(merged.versions[0].previous.as_deref(), Some("1.0.0"));
+        assert_eq!(merged.versions[1].previous, None);
+    }
+
+    #[test]
+    fn releasing_the_same_entries_on_both_sides_conflicts() {
+        let base = changelog(&["a"], vec![Version::fixture("1.0.0", &[])]);
+        let ours = changelog(
+            &[],
+            vec![
+                Version::fixture("1.1.0", &["a"]),
+                Version::fixture("1.0.0", &[]),
+            ],
+        );
+        let theirs = changelog(
+            &[],
+            vec![
+                Version::fixture("1.0.1", &["a"]),
+                Version::fixture("1.0.0", &[]),
+            ],
+        );
+
+        let conflicts = merge(&base, &ours, &theirs).unwrap_err();
+        assert_eq!(
+            conflicts,
+            [Conflict {
+                path: "versions.1.1.0".to_string(),
+                message: "releases \"a\" from unreleased, as does version 1.0.1 on the other side"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn clashing_versions_conflict() {
+        let base = changelog(&[], vec![]);
+        let ours = changelog(&[], vec![Version::fixture("1.0.0", &["a"])]);
+        let theirs = changelog(&[], vec![Version::fixture("1.0.0", &["b"])]);
+
+        let conflicts = merge(&base, &ours, &theirs).unwrap_err();
+        assert_eq!(conflicts[0].path, "versions.1.0.0");
+    }
+}
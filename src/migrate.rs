@@ -0,0 +1,110 @@
+//! Versioning of the changelog source format
+//!
+//! Sources record the format version they were written in with `schema_version`.
+//! When the format changes, a migration is added here to upgrade older sources.
+
+use anyhow::{anyhow, bail};
+use serde_json::{Map, Value};
+
+use crate::Changelog;
+
+/// The current version of the changelog source format
+pub const SCHEMA_VERSION: &str = "1.1";
+
+/// A version assumed for sources that don't record one
+const INITIAL_VERSION: &str = "1.0";
+
+type Migration = fn(&mut Map<String, Value>) -> anyhow::Result<()>;
+
+/// Each migration upgrades a source from one version to the next
+const MIGRATIONS: &[(&str, &str, Migration)] = &[("1.0", "1.1", migrate_1_0_to_1_1)];
+
+/// The canonical url of a version of the JSON Schema
+pub fn schema_url(version: &str) -> String {
+    format!("https://changelog-md.github.io/{}/changelog", version)
+}
+
+/// The format version a parsed source was written in
+///
+/// Uses `schema_version` if present, falling back to a versioned `$schema` url.
+pub fn source_version(source: &Value) -> String {
+    if let Some(version) = source.get("schema_version").and_then(Value::as_str) {
+        return version.to_string();
+    }
+
+    source
+        .get("$schema")
+        .and_then(Value::as_str)
+        .and_then(|url| {
+            url.strip_prefix("https://changelog-md.github.io/")?
+                .strip_suffix("/changelog")
+        })
+        .unwrap_or(INITIAL_VERSION)
+        .to_string()
+}
+
+/// Upgrade a parsed source to the current format
+///
+/// Returns the upgraded changelog, and the version migrations that were applied
+pub fn migrate(mut source: Value) -> anyhow::Result<(Changelog, Vec<String>)> {
+    let mut version = source_version(&source);
+    let mut applied = vec![];
+
+    let object = source
+        .as_object_mut()
+        .ok_or(anyhow!("A changelog source must be a map"))?;
+
+    while version != SCHEMA_VERSION {
+        let Some((from, to, migration)) = MIGRATIONS.iter().find(|(from, ..)| *from == version)
+        else {
+            bail!(
+                "Unknown schema version {}, the latest supported version is {}",
+                version,
+                SCHEMA_VERSION
+            );
+        };
+        migration(object)?;
+        applied.push(format!("{} -> {}", from, to));
+        version = to.to_string();
+    }
+
+    let changelog = serde_path_to_error::deserialize(source)?;
+    Ok((changelog, applied))
+}
+
+/// 1.1 introduced `$schema` and `schema_version`
+fn migrate_1_0_to_1_1(source: &mut Map<String, Value>) -> anyhow::Result<()> {
+    if source.get("$schema").and_then(Value::as_str) == Some(&schema_url("1.0")) {
+        source.insert("$schema".into(), schema_url("1.1").into());
+    }
+    source.insert("schema_version".into(), "1.1".into());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn migrates_unversioned_sources() {
+        let source = serde_json::json!({
+            "$schema": schema_url("1.0"),
+            "title": "Changelog",
+            "description": "",
+            "repository": "",
+            "unreleased": {},
+            "versions": {},
+        });
+
+        let (changelog, applied) = migrate(source).unwrap();
+        assert_eq!(applied, ["1.0 -> 1.1"]);
+        assert_eq!(changelog.schema_version.as_deref(), Some(SCHEMA_VERSION));
+        assert_eq!(changelog.schema, Some(schema_url(SCHEMA_VERSION)));
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let source = serde_json::json!({ "schema_version": "9.0" });
+        assert!(migrate(source).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+//! Rendering one Markdown page per release, for documentation site generators such as
+//! mdBook and Hugo
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use crate::{Changelog, RenderOptions, Version, render_to_string};
+
+/// Syntax of the front matter at the top of each page
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FrontMatter {
+    /// YAML between `---` lines
+    #[default]
+    Yaml,
+    /// TOML between `+++` lines, as preferred by Hugo
+    Toml,
+    /// No front matter
+    None,
+}
+
+/// Options controlling how a changelog is split into pages
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SplitOptions {
+    /// Front matter syntax
+    pub front_matter: FrontMatter,
+    /// Title of each release page, with `{version}` and `{date}` replaced
+    pub title: String,
+    /// Write the release date to the front matter
+    pub date: bool,
+    /// Write a `weight` to the front matter, ordering pages newest first
+    pub weight: bool,
+    /// Tags written to the front matter of every release page
+    pub tags: Vec<String>,
+    /// File name of the index page, e.g. `_index.md` for a Hugo section
+    pub index: String,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        Self {
+            front_matter: FrontMatter::Yaml,
+            title: "{version}".to_string(),
+            date: true,
+            weight: true,
+            tags: vec![],
+            index: "index.md".to_string(),
+        }
+    }
+}
+
+/// A page of a split changelog
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// File name, relative to the output directory
+    pub file_name: String,
+    /// Markdown contents, including any front matter
+    pub contents: String,
+}
+
+/// The slug of a version's page, e.g. `1-2-0-rc-1` for `1.2.0-rc.1`
+///
+/// Slugs only depend on the version name, so links to a page keep working as releases are added.
+pub fn slug(version: &str) -> String {
+    let mut slug = String::new();
+    for c in version.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    match slug.is_empty() {
+        true => "version".to_string(),
+        false => slug.to_string(),
+    }
+}
+
+/// Front matter fields, written in order
+struct Fields<'a> {
+    title: String,
+    date: Option<&'a str>,
+    weight: Option<usize>,
+    tags: &'a [String],
+}
+
+impl Changelog {
+    /// Render an index page, an unreleased page, and a page per release selected by `options`
+    ///
+    /// Versions whose slugs clash, such as `1.0.0+build` and `1.0.0-build`, are an error.
+    pub fn render_split(
+        &self,
+        options: &RenderOptions,
+        split: &SplitOptions,
+    ) -> anyhow::Result<Vec<Page>> {
+        let versions = self.rendered_versions(options);
+        let mut pages = vec![];
+        let page = |file_name: String, fields: Fields, body: String| {
+            let markdown =
+                front_matter(split.front_matter, &fields) + body.trim_start_matches('\n');
+            Page {
+                file_name,
+                contents: self.autolink(markdown, options),
+            }
+        };
+
+        let index = render_to_string(|f| self.write_index(f, &versions));
+        let fields = Fields {
+            title: self.title.clone(),
+            date: None,
+            weight: None,
+            tags: &[],
+        };
+        pages.push(page(split.index.clone(), fields, index));
+
+        let mut title = "Unreleased".to_string();
+        if let Some(next) = &self.next {
+            title += &render_to_string(|f| next.write_heading(f));
+        }
+        let unreleased = render_to_string(|f| self.write_unreleased_page(f, options));
+        let fields = Fields {
+            title,
+            date: None,
+            weight: split.weight.then_some(1),
+            tags: &[],
+        };
+        pages.push(page("unreleased.md".to_string(), fields, unreleased));
+
+        let mut written = HashMap::from([
+            (split.index.clone(), "the index"),
+            ("unreleased.md".to_string(), "unreleased changes"),
+        ]);
+        for (idx, version) in versions.iter().enumerate() {
+            let file_name = format!("{}.md", slug(&version.version));
+            if let Some(other) = written.insert(file_name.clone(), &version.version) {
+                bail!(
+                    "{} and {} would both be written to {}",
+                    other,
+                    version.version,
+                    file_name
+                );
+            }
+
+            let body = render_to_string(|f| write_version_page(f, version, options));
+            let fields = Fields {
+                title: split
+                    .title
+                    .replace("{version}", &version.version)
+                    .replace("{date}", &version.date),
+                date: split.date.then_some(version.date.as_str()),
+                weight: split.weight.then_some(idx + 2),
+                tags: &split.tags,
+            };
+            pages.push(page(file_name, fields, body));
+        }
+        Ok(pages)
+    }
+
+    // The description, followed by links to every page
+    fn write_index(&self, f: &mut impl Write, versions: &[&Version]) -> std::fmt::Result {
+        writeln!(f, "{}", self.description.trim())?;
+        writeln!(f)?;
+        writeln!(f, "- [Unreleased](unreleased.md)")?;
+        for version in versions {
+            write!(
+                f,
+                "- [{}]({}.md) - {}",
+                version.version,
+                slug(&version.version),
+                version.date
+            )?;
+            if version.yanked.is_some() {
+                write!(f, " [YANKED]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+
+    // The planned release's theme, then unreleased changes with a section per track
+    fn write_unreleased_page(
+        &self,
+        f: &mut impl Write,
+        options: &RenderOptions,
+    ) -> std::fmt::Result {
+        if let Some(theme) = self.next.as_ref().and_then(|next| next.theme.as_ref()) {
+            writeln!(f, "{}", theme.trim())?;
+        }
+        let changes = &self.unreleased.changes;
+        changes.write_breaking(f, None, options)?;
+        changes.write_markdown(f, options)?;
+        for (track, changes) in &self.unreleased.tracks {
+            if changes.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            writeln!(f, "## {}", track)?;
+            changes.write_breaking(f, None, options)?;
+            changes.write_markdown(f, options)?;
+        }
+        Ok(())
+    }
+}
+
+// The yank reason, then the version's description and changes
+fn write_version_page(
+    f: &mut impl Write,
+    version: &Version,
+    options: &RenderOptions,
+) -> std::fmt::Result {
+    if let Some(reason) = &version.yanked {
+        writeln!(f, "**Yanked:** {}", reason.trim())?;
+        // Everything else the body starts with begins with a blank line
+        if version.description.is_some() {
+            writeln!(f)?;
+        }
+    }
+    version.write_body(f, options)
+}
+
+/// Front matter as YAML or TOML, quoting strings as JSON which both accept
+fn front_matter(format: FrontMatter, fields: &Fields) -> String {
+    let quote = |value: &str| serde_json::to_string(value).expect("strings always serialize");
+    let (fence, separator) = match format {
+        FrontMatter::Yaml => ("---", ": "),
+        FrontMatter::Toml => ("+++", " = "),
+        FrontMatter::None => return String::new(),
+    };
+
+    let mut out = format!("{}\n", fence);
+    out += &format!("title{}{}\n", separator, quote(&fields.title));
+    if let Some(date) = fields.date {
+        // Valid dates are left unquoted, so that both read them as dates
+        match date.parse::<crate::Date>() {
+            Ok(_) => out += &format!("date{}{}\n", separator, date),
+            Err(_) => out += &format!("date{}{}\n", separator, quote(date)),
+        }
+    }
+    if let Some(weight) = fields.weight {
+        out += &format!("weight{}{}\n", separator, weight);
+    }
+    if !fields.tags.is_empty() {
+        let tags = fields.tags.iter().map(|tag| quote(tag)).collect::<Vec<_>>();
+        out += &format!("tags{}[{}]\n", separator, tags.join(", "));
+    }
+    out += &format!("{}\n\n", fence);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Changes;
+
+    #[test]
+    fn slugs_are_stable() {
+        assert_eq!(slug("1.2.0"), "1-2-0");
+        assert_eq!(slug("v2.0.0-RC.1"), "v2-0-0-rc-1");
+        assert_eq!(slug("2025.03.1+build"), "2025-03-1-build");
+        assert_eq!(slug("..."), "version");
+    }
+
+    #[test]
+    fn renders_a_page_per_version() {
+        let changelog = Changelog {
+            unreleased: Changes::default().into(),
+            versions: vec![
+                Version {
+                    version: "1.1.0".to_string(),
+                    tag: "v1.1.0".to_string(),
+                    date: "2025-02-01".to_string(),
+                    yanked: Some("Broke the build".to_string()),
+                    changes: Changes {
+                        fixed: vec!["A fix".into()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Version {
+                    version: "1.0.0".to_string(),
+                    tag: "v1.0.0".to_string(),
+                    date: "2025-01-01".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let split = SplitOptions {
+            front_matter: FrontMatter::Toml,
+            title: "Release {version}".to_string(),
+            tags: vec!["release".to_string()],
+            ..Default::default()
+        };
+        let pages = changelog
+            .render_split(&RenderOptions::default(), &split)
+            .unwrap();
+
+        let names = pages
+            .iter()
+            .map(|page| page.file_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["index.md", "unreleased.md", "1-1-0.md", "1-0-0.md"]);
+        assert!(
+            pages[0]
+                .contents
+                .contains("- [1.1.0](1-1-0.md) - 2025-02-01 [YANKED]\n")
+        );
+        assert_eq!(
+            pages[2].contents,
+            "+++\n\
+             title = \"Release 1.1.0\"\n\
+             date = 2025-02-01\n\
+             weight = 2\n\
+             tags = [\"release\"]\n\
+             +++\n\
+             \n\
+             **Yanked:** Broke the build\n\
+             \n\
+             ### Fixed\n\
+             \n\
+             - A fix\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn clashing_slugs_are_an_error() {
+        let changelog = Changelog {
+            versions: vec![
+                Version::fixture("1.0.0+build", &[]),
+                Version::fixture("1.0.0-build", &[]),
+            ],
+            ..Default::default()
+        };
+        let err = changelog
+            .render_split(&RenderOptions::default(), &SplitOptions::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1.0.0+build and 1.0.0-build would both be written to 1-0-0-build.md"
+        );
+    }
+}
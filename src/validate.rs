@@ -0,0 +1,678 @@
+//! Checks on a changelog's contents beyond what its schema can express
+
+use std::path::Path;
+
+#[cfg(feature = "markdown")]
+use pulldown_cmark::{BrokenLink, Event, LinkType, Parser, Tag, TagEnd};
+
+use crate::{Category, Changelog, Changes, Date, Entry, VersionNumber};
+
+/// How serious a validation problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The changelog can still be rendered, but is probably wrong
+    Warning,
+    /// The changelog is invalid
+    Error,
+}
+
+/// A problem found while validating a changelog
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Path to the offending field, e.g. `versions.1.2.0.previous`
+    pub path: String,
+    /// Human readable description of the problem
+    pub message: String,
+}
+
+impl Severity {
+    /// The lowercase name of this severity
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}",
+            self.severity.name(),
+            self.path,
+            self.message
+        )
+    }
+}
+
+/// Where a Markdown fragment is rendered, deciding which headings it may use
+#[cfg(feature = "markdown")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fragment {
+    /// A list item, which cannot contain headings
+    Entry,
+    /// A description, below a heading of the given level
+    Description(pulldown_cmark::HeadingLevel),
+}
+
+impl Changelog {
+    /// Check the changelog for problems, such as broken release lineage or Markdown
+    ///
+    /// Markdown is only checked with the `markdown` feature. Relative links are checked against
+    /// `base`, the directory holding the changelog source, and are not checked without it.
+    /// Tags are checked against `tag_template`, or `default_template` such as the configured
+    /// `tag-template` when it is unset.
+    pub fn validate(&self, base: Option<&Path>, default_template: Option<&str>) -> Vec<Problem> {
+        let mut problems = vec![];
+        self.validate_versions(&mut problems);
+        self.validate_dates(&mut problems);
+        self.validate_tags(default_template, &mut problems);
+        self.validate_lineage(&mut problems);
+        self.validate_tracks(&mut problems);
+        self.validate_advisories(&mut problems);
+        #[cfg(feature = "markdown")]
+        self.validate_markdown(base, &mut problems);
+        #[cfg(not(feature = "markdown"))]
+        let _ = base;
+        problems
+    }
+
+    /// Unreleased and released changes, with the path to each
+    fn changes_with_paths(&self) -> impl Iterator<Item = (String, &Changes)> {
+        let unreleased = self
+            .unreleased
+            .all_tracks()
+            .map(|(track, changes)| match track {
+                Some(track) => (format!("unreleased.tracks.{}", track), changes),
+                None => ("unreleased".to_string(), changes),
+            });
+        unreleased.chain(
+            self.versions
+                .iter()
+                .map(|v| (format!("versions.{}", v.version), &v.changes)),
+        )
+    }
+
+    #[cfg(feature = "markdown")]
+    fn validate_markdown(&self, base: Option<&Path>, problems: &mut Vec<Problem>) {
+        use pulldown_cmark::HeadingLevel::{H1, H2};
+
+        let mut check = |path: String, markdown: &str, fragment: Fragment| {
+            check_markdown(&path, markdown, fragment, base, problems)
+        };
+
+        check(
+            "description".to_string(),
+            &self.description,
+            Fragment::Description(H1),
+        );
+        if let Some(theme) = self.next.as_ref().and_then(|next| next.theme.as_ref()) {
+            check("next.theme".to_string(), theme, Fragment::Description(H2));
+        }
+        for version in &self.versions {
+            let path = format!("versions.{}", version.version);
+            if let Some(description) = &version.description {
+                check(
+                    format!("{}.description", path),
+                    description,
+                    Fragment::Description(H2),
+                );
+            }
+            if let Some(migration) = &version.migration {
+                check(
+                    format!("{}.migration", path),
+                    migration,
+                    Fragment::Description(H2),
+                );
+            }
+        }
+
+        fn check_entries(
+            path: &str,
+            entries: &[Entry],
+            check: &mut impl FnMut(String, &str, Fragment),
+        ) {
+            for (idx, entry) in entries.iter().enumerate() {
+                let path = format!("{}[{}]", path, idx);
+                check(path.clone(), &entry.text, Fragment::Entry);
+                check_entries(&format!("{}.children", path), &entry.children, check);
+            }
+        }
+        for (path, changes) in self.changes_with_paths() {
+            for category in Category::ALL {
+                let path = format!("{}.{}", path, category.key());
+                check_entries(&path, changes.get(category), &mut check);
+            }
+        }
+    }
+
+    fn validate_advisories(&self, problems: &mut Vec<Problem>) {
+        fn check_entries(path: &str, entries: &[Entry], problems: &mut Vec<Problem>) {
+            for (idx, entry) in entries.iter().enumerate() {
+                let path = format!("{}[{}]", path, idx);
+                if entry.advisory.is_some() {
+                    problems.push(Problem {
+                        severity: Severity::Warning,
+                        path: path.clone(),
+                        message: "advisories are only exported from security entries".to_string(),
+                    });
+                }
+                check_entries(&format!("{}.children", path), &entry.children, problems);
+            }
+        }
+
+        for (path, changes) in self.changes_with_paths() {
+            for category in Category::ALL {
+                if category == Category::Security {
+                    continue;
+                }
+                let path = format!("{}.{}", path, category.key());
+                check_entries(&path, changes.get(category), problems);
+            }
+        }
+    }
+
+    fn validate_tracks(&self, problems: &mut Vec<Problem>) {
+        for track in self.unreleased.tracks.keys() {
+            if Category::from_key(track).is_some() {
+                problems.push(Problem {
+                    severity: Severity::Error,
+                    path: format!("unreleased.tracks.{}", track),
+                    message: format!("tracks can't be named after the {} category", track),
+                });
+            }
+        }
+    }
+
+    fn validate_versions(&self, problems: &mut Vec<Problem>) {
+        let planned = self.next.as_ref().and_then(|next| next.version.as_ref());
+        if let Some(planned) = planned
+            && self.all_versions().any(|v| &v.version == planned)
+        {
+            problems.push(Problem {
+                severity: Severity::Error,
+                path: "next.version".to_string(),
+                message: format!("{} is already released", planned),
+            });
+        }
+
+        let Some(scheme) = self.version_scheme else {
+            return;
+        };
+        if let Some(planned) = planned
+            && let Err(err) = VersionNumber::parse_as(planned, scheme)
+        {
+            problems.push(Problem {
+                severity: Severity::Error,
+                path: "next.version".to_string(),
+                message: err.to_string(),
+            });
+        }
+        for version in &self.versions {
+            if let Err(err) = VersionNumber::parse_as(&version.version, scheme) {
+                problems.push(Problem {
+                    severity: Severity::Error,
+                    path: format!("versions.{}", version.version),
+                    message: err.to_string(),
+                });
+            }
+        }
+    }
+
+    // Check release tags follow `tag_template`, or `default_template` when it is unset
+    fn validate_tags(&self, default_template: Option<&str>, problems: &mut Vec<Problem>) {
+        let Some(template) = self.tag_template.as_deref().or(default_template) else {
+            return;
+        };
+        if let Err(err) = self.check_tag_template(template) {
+            problems.push(Problem {
+                severity: Severity::Error,
+                path: "tag_template".to_string(),
+                message: err.to_string(),
+            });
+            return;
+        }
+        for version in &self.versions {
+            let Ok(expected) = self.tag_for(&version.version, default_template) else {
+                continue;
+            };
+            if version.tag != expected {
+                problems.push(Problem {
+                    severity: Severity::Warning,
+                    path: format!("versions.{}.tag", version.version),
+                    message: format!(
+                        "tag {} does not match the template {}, expected {}",
+                        version.tag, template, expected
+                    ),
+                });
+            }
+        }
+    }
+
+    fn validate_dates(&self, problems: &mut Vec<Problem>) {
+        for version in &self.versions {
+            if let Err(err) = version.release_date() {
+                problems.push(Problem {
+                    severity: Severity::Error,
+                    path: format!("versions.{}.date", version.version),
+                    message: err.to_string(),
+                });
+            }
+        }
+
+        // Targets may name a month rather than a day
+        let target = self.next.as_ref().and_then(|next| next.target.as_ref());
+        if let Some(target) = target
+            && target.parse::<Date>().is_err()
+            && format!("{}-01", target).parse::<Date>().is_err()
+        {
+            problems.push(Problem {
+                severity: Severity::Error,
+                path: "next.target".to_string(),
+                message: format!(
+                    "invalid target `{}`, expected YYYY-MM-DD or YYYY-MM",
+                    target
+                ),
+            });
+        }
+    }
+
+    fn validate_lineage(&self, problems: &mut Vec<Problem>) {
+        for version in &self.versions {
+            let Some(previous) = &version.previous else {
+                continue;
+            };
+            let path = format!("versions.{}.previous", version.version);
+            if previous == &version.version {
+                problems.push(Problem {
+                    severity: Severity::Error,
+                    path,
+                    message: "a version cannot follow itself".to_string(),
+                });
+            } else if !self.all_versions().any(|v| &v.version == previous) {
+                problems.push(Problem {
+                    severity: Severity::Error,
+                    path,
+                    message: format!("unknown version {}", previous),
+                });
+            } else if self.lineage_has_cycle(&version.version) {
+                problems.push(Problem {
+                    severity: Severity::Error,
+                    path,
+                    message: "release lineage loops back to this version".to_string(),
+                });
+            }
+        }
+    }
+
+    fn lineage_has_cycle(&self, start: &str) -> bool {
+        let mut current = self.all_versions().find(|v| v.version == start);
+        for _ in 0..self.all_versions().count() {
+            current = current.and_then(|v| self.previous_version(v));
+            match current {
+                Some(v) if v.version == start => return true,
+                Some(_) => {}
+                None => return false,
+            }
+        }
+        false
+    }
+}
+
+/// Report structural problems in a Markdown fragment, which would break the rendered page
+#[cfg(feature = "markdown")]
+fn check_markdown(
+    path: &str,
+    markdown: &str,
+    fragment: Fragment,
+    base: Option<&Path>,
+    problems: &mut Vec<Problem>,
+) {
+    let mut report = |severity, message: String| {
+        problems.push(Problem {
+            severity,
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    let mut broken = vec![];
+    let mut callback = |link: BrokenLink| {
+        if link.link_type != LinkType::Shortcut {
+            broken.push(link.reference.to_string());
+        }
+        None
+    };
+    let parser = Parser::new_with_broken_link_callback(
+        markdown,
+        pulldown_cmark::Options::empty(),
+        Some(&mut callback),
+    );
+
+    let mut in_code_block = false;
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(Tag::Heading { level, .. }) => match fragment {
+                Fragment::Entry => report(
+                    Severity::Error,
+                    "headings cannot be used within an entry".to_string(),
+                ),
+                Fragment::Description(parent) if level <= parent => report(
+                    Severity::Error,
+                    format!(
+                        "{} heading would be rendered as part of the page structure, use a lower level",
+                        level
+                    ),
+                ),
+                _ => {}
+            },
+            Event::Text(_) if !in_code_block => {
+                let unescaped = markdown[range.clone()]
+                    .char_indices()
+                    .any(|(idx, c)| c == '`' && !markdown[..range.start + idx].ends_with('\\'));
+                if unescaped {
+                    report(Severity::Error, "unbalanced backtick".to_string());
+                }
+            }
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                if let Some(base) = base
+                    && let Some(target) = relative_target(&dest_url)
+                    && !resolve_link(base, target).exists()
+                {
+                    report(
+                        Severity::Error,
+                        format!("relative link to {} does not exist", target),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for reference in broken {
+        report(
+            Severity::Error,
+            format!("reference link [{}] has no definition", reference),
+        );
+    }
+}
+
+/// The file a relative link points to, without any fragment or query
+#[cfg(feature = "markdown")]
+fn relative_target(url: &str) -> Option<&str> {
+    let target = url.split(['#', '?']).next().unwrap_or_default();
+    let absolute = url.contains("://") || url.starts_with("mailto:") || url.starts_with("//");
+    (!absolute && !target.is_empty()).then_some(target)
+}
+
+/// Resolve a relative link from `base`, with `/` links resolved from the repository root
+#[cfg(feature = "markdown")]
+fn resolve_link(base: &Path, target: &str) -> std::path::PathBuf {
+    match target.strip_prefix('/') {
+        Some(target) => base
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(base)
+            .join(target),
+        None => base.join(target),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Version;
+
+    fn version(version: &str, previous: Option<&str>) -> Version {
+        Version {
+            previous: previous.map(str::to_string),
+            ..Version::fixture(version, &[])
+        }
+    }
+
+    #[test]
+    fn reports_invalid_dates() {
+        let changelog = Changelog {
+            versions: vec![
+                Version {
+                    date: "2025-02-30".to_string(),
+                    ..version("1.1.0", None)
+                },
+                Version {
+                    date: "01/02/2025".to_string(),
+                    ..version("1.0.0", None)
+                },
+            ],
+            ..Default::default()
+        };
+        let paths = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|problem| problem.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["versions.1.1.0.date", "versions.1.0.0.date"]);
+    }
+
+    #[test]
+    fn reports_versions_not_following_the_scheme() {
+        let mut changelog = Changelog {
+            versions: vec![
+                version("1.0.0rc1", None),
+                version("1.0", None),
+                version("0.9.0", None),
+            ],
+            ..Default::default()
+        };
+        assert!(changelog.validate(None, None).is_empty());
+
+        changelog.version_scheme = Some(crate::Scheme::Semver);
+        let problems = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            [
+                "error: versions.1.0.0rc1: `1.0.0rc1` is not a valid semver version",
+                "error: versions.1.0: `1.0` is not a valid semver version",
+            ]
+        );
+
+        changelog.version_scheme = Some(crate::Scheme::Pep440);
+        assert!(changelog.validate(None, None).is_empty());
+    }
+
+    #[test]
+    fn reports_tags_not_following_the_template() {
+        let mut changelog = Changelog {
+            versions: vec![
+                Version {
+                    tag: "core-v1.1.0".to_string(),
+                    ..version("1.1.0", None)
+                },
+                version("1.0.0", None),
+            ],
+            ..Default::default()
+        };
+        assert!(changelog.validate(None, None).is_empty());
+        assert_eq!(changelog.validate(None, Some("v{version}")).len(), 2);
+
+        changelog.tag_template = Some("{component}-v{version}".to_string());
+        let problems = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            [
+                "error: tag_template: the tag template {component}-v{version} uses {component}, but `component` is not set"
+            ]
+        );
+
+        changelog.component = Some("core".to_string());
+        let problems = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            [
+                "warning: versions.1.0.0.tag: tag 1.0.0 does not match the template {component}-v{version}, expected core-v1.0.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_plans() {
+        let changelog = Changelog {
+            next: Some(crate::Next {
+                version: Some("1.0.0".to_string()),
+                target: Some("2025-6".to_string()),
+                theme: Some("## Stability".to_string()),
+            }),
+            versions: vec![version("1.0.0", None)],
+            ..Default::default()
+        };
+        let paths = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|problem| problem.path)
+            .collect::<Vec<_>>();
+        let mut expected = vec!["next.version", "next.target"];
+        if cfg!(feature = "markdown") {
+            expected.push("next.theme");
+        }
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "markdown")]
+    fn reports_markdown_problems() {
+        let changelog = Changelog {
+            description: "# Overview".to_string(),
+            versions: vec![Version {
+                version: "1.2.0".to_string(),
+                changes: Changes {
+                    fixed: vec![
+                        "Handle `None` and \\` literally".into(),
+                        "See [the guide](README.md#usage) and [docs][]".into(),
+                        "Broken `code".into(),
+                        Entry {
+                            text: "Rendering".to_string(),
+                            children: vec!["# Heading".into(), "[Missing](missing.md)".into()],
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                },
+                ..version("1.2.0", None)
+            }],
+            ..Default::default()
+        };
+        let problems = changelog
+            .validate(Some(Path::new(env!("CARGO_MANIFEST_DIR"))), None)
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            [
+                "error: description: h1 heading would be rendered as part of the page structure, use a lower level",
+                "error: versions.1.2.0.fixed[1]: reference link [docs] has no definition",
+                "error: versions.1.2.0.fixed[2]: unbalanced backtick",
+                "error: versions.1.2.0.fixed[3].children[0]: headings cannot be used within an entry",
+                "error: versions.1.2.0.fixed[3].children[1]: relative link to missing.md does not exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_broken_lineage() {
+        let changelog = Changelog {
+            versions: vec![
+                version("2.0.0", Some("0.9.0")),
+                version("1.1.0", Some("1.1.0")),
+                version("1.0.0", None),
+            ],
+            ..Default::default()
+        };
+
+        let paths = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|p| p.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            ["versions.2.0.0.previous", "versions.1.1.0.previous"]
+        );
+    }
+
+    #[test]
+    fn reports_lineage_cycles() {
+        let changelog = Changelog {
+            versions: vec![version("2.0.0", Some("1.0.0")), version("1.0.0", None)],
+            ..Default::default()
+        };
+        assert!(changelog.validate(None, None).is_empty());
+
+        let changelog = Changelog {
+            versions: vec![
+                version("2.0.0", Some("1.0.0")),
+                version("1.0.0", Some("2.0.0")),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(changelog.validate(None, None).len(), 2);
+    }
+
+    #[test]
+    fn reports_tracks_named_after_categories() {
+        let mut changelog = Changelog::default();
+        for track in ["next-major", "fixed"] {
+            changelog
+                .unreleased
+                .tracks
+                .insert(track.to_string(), Default::default());
+        }
+        let paths = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|p| p.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["unreleased.tracks.fixed"]);
+        assert!(changelog.unreleased.track_mut(Some("fixed")).is_err());
+    }
+
+    #[test]
+    fn reports_advisories_outside_security() {
+        let advisory = Entry {
+            text: "Fix".to_string(),
+            advisory: Some(Default::default()),
+            ..Default::default()
+        };
+        let mut changelog = Changelog::default();
+        changelog.unreleased.changes.fixed = vec![Entry {
+            text: "Fixes".to_string(),
+            children: vec![advisory.clone()],
+            ..Default::default()
+        }];
+        changelog.unreleased.changes.security = vec![advisory];
+
+        let paths = changelog
+            .validate(None, None)
+            .into_iter()
+            .map(|p| p.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["unreleased.fixed[0].children[0]"]);
+    }
+}
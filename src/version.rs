@@ -0,0 +1,552 @@
+//! Version numbers, parsed according to the scheme they follow
+
+use std::cmp::Ordering;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+/// A versioning scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum Scheme {
+    /// [Semantic versioning](https://semver.org), e.g. `1.2.3-rc.1`
+    Semver,
+    /// [Calendar versioning](https://calver.org), starting with a four digit year, e.g. `2025.03.1`
+    Calver,
+    /// [PEP 440](https://peps.python.org/pep-0440/) Python versions, e.g. `1.2.0rc1.post2`
+    Pep440,
+}
+
+impl Scheme {
+    /// The name of this scheme, as written in changelog sources
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scheme::Semver => "semver",
+            Scheme::Calver => "calver",
+            Scheme::Pep440 => "pep440",
+        }
+    }
+}
+
+/// Which part of a version to increment for the next release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    /// Incompatible changes, e.g. `1.2.3` to `2.0.0`
+    Major,
+    /// New features, e.g. `1.2.3` to `1.3.0`
+    Minor,
+    /// Fixes only, e.g. `1.2.3` to `1.2.4`
+    Patch,
+}
+
+/// A version number, keeping the text it was written as
+///
+/// Numbers are ordered by the precedence rules of their scheme,
+/// or component by component when they follow no known scheme.
+#[derive(Debug, Clone)]
+pub struct VersionNumber {
+    text: String,
+    parsed: Parsed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Parsed {
+    Semver {
+        release: [u64; 3],
+        pre: Vec<String>,
+    },
+    Calver {
+        release: Vec<u64>,
+        modifier: Option<String>,
+    },
+    Pep440 {
+        epoch: u64,
+        release: Vec<u64>,
+        /// Phase, `a` = 0, `b` = 1 and `rc` = 2, and number
+        pre: Option<(u8, u64)>,
+        post: Option<u64>,
+        dev: Option<u64>,
+    },
+    Other,
+}
+
+impl VersionNumber {
+    /// Parse a version number, detecting its scheme
+    ///
+    /// Versions starting with a four digit year are calendar versions,
+    /// and anything that isn't semver or calver is kept as free-form text.
+    /// PEP 440 versions overlap with both, so are only recognised by [`VersionNumber::parse_as`].
+    pub fn parse(text: &str) -> VersionNumber {
+        let parsed = parse_calver(text)
+            .or_else(|| parse_semver(text))
+            .unwrap_or(Parsed::Other);
+        VersionNumber {
+            text: text.to_string(),
+            parsed,
+        }
+    }
+
+    /// Parse a version number following `scheme`, failing if it doesn't
+    pub fn parse_as(text: &str, scheme: Scheme) -> anyhow::Result<VersionNumber> {
+        let parsed = match scheme {
+            Scheme::Semver => parse_semver(text),
+            Scheme::Calver => parse_calver(text),
+            Scheme::Pep440 => parse_pep440(text),
+        };
+        match parsed {
+            Some(parsed) => Ok(VersionNumber {
+                text: text.to_string(),
+                parsed,
+            }),
+            None => bail!("`{}` is not a valid {} version", text, scheme.name()),
+        }
+    }
+
+    /// The numeric release parts, e.g. `[1, 2, 3]` for `1.2.3-rc.1`, empty for free-form versions
+    pub fn release(&self) -> &[u64] {
+        match &self.parsed {
+            Parsed::Semver { release, .. } => release,
+            Parsed::Calver { release, .. } | Parsed::Pep440 { release, .. } => release,
+            Parsed::Other => &[],
+        }
+    }
+
+    /// The scheme this version follows, or `None` for free-form versions
+    pub fn scheme(&self) -> Option<Scheme> {
+        match self.parsed {
+            Parsed::Semver { .. } => Some(Scheme::Semver),
+            Parsed::Calver { .. } => Some(Scheme::Calver),
+            Parsed::Pep440 { .. } => Some(Scheme::Pep440),
+            Parsed::Other => None,
+        }
+    }
+
+    /// Whether this is a pre-release, such as `1.0.0-rc.1` or `1.0.0.dev1`
+    pub fn is_prerelease(&self) -> bool {
+        match &self.parsed {
+            Parsed::Semver { pre, .. } => !pre.is_empty(),
+            Parsed::Calver { modifier, .. } => modifier.is_some(),
+            Parsed::Pep440 { pre, dev, .. } => pre.is_some() || dev.is_some(),
+            Parsed::Other => false,
+        }
+    }
+
+    /// The next version after this one, or `None` for calendar and free-form versions
+    ///
+    /// A pre-release is bumped to its own release where that is high enough,
+    /// so `2.0.0-rc.1` bumps to `2.0.0` for a major release.
+    pub fn bump(&self, bump: Bump) -> Option<VersionNumber> {
+        let (release, prerelease) = match &self.parsed {
+            Parsed::Semver { release, .. } => (release.to_vec(), self.is_prerelease()),
+            Parsed::Pep440 { release, .. } => (release.clone(), self.is_prerelease()),
+            Parsed::Calver { .. } | Parsed::Other => return None,
+        };
+        let part = |idx: usize| release.get(idx).copied().unwrap_or(0);
+        let (major, minor, patch) = (part(0), part(1), part(2));
+        let next = match bump {
+            Bump::Major if prerelease && minor == 0 && patch == 0 => [major, 0, 0],
+            Bump::Major => [major + 1, 0, 0],
+            Bump::Minor if prerelease && patch == 0 => [major, minor, 0],
+            Bump::Minor => [major, minor + 1, 0],
+            Bump::Patch if prerelease => [major, minor, patch],
+            Bump::Patch => [major, minor, patch + 1],
+        };
+
+        let prefix = match &self.parsed {
+            Parsed::Pep440 { epoch, .. } if *epoch > 0 => format!("{}!", epoch),
+            _ => self.text[..self.text.len() - strip_prefix(&self.text).len()].to_string(),
+        };
+        let text = format!("{}{}.{}.{}", prefix, next[0], next[1], next[2]);
+        VersionNumber::parse_as(&text, self.scheme()?).ok()
+    }
+
+    /// The version as written
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Compare by precedence alone, so `v1.0.0` and `1.0.0+build` equal `1.0.0`
+    pub fn cmp_precedence(&self, other: &VersionNumber) -> Ordering {
+        match (&self.parsed, &other.parsed) {
+            (
+                Parsed::Semver { release, pre },
+                Parsed::Semver {
+                    release: other_release,
+                    pre: other_pre,
+                },
+            ) => release
+                .cmp(other_release)
+                .then_with(|| compare_prerelease(pre, other_pre)),
+            (
+                Parsed::Calver { release, modifier },
+                Parsed::Calver {
+                    release: other_release,
+                    modifier: other_modifier,
+                },
+            ) => release
+                .cmp(other_release)
+                .then_with(|| match (modifier, other_modifier) {
+                    (None, None) => Ordering::Equal,
+                    (None, Some(_)) => Ordering::Greater,
+                    (Some(_), None) => Ordering::Less,
+                    (Some(a), Some(b)) => natural_cmp(a, b),
+                }),
+            (a @ Parsed::Pep440 { .. }, b @ Parsed::Pep440 { .. }) => {
+                pep440_key(a).cmp(&pep440_key(b))
+            }
+            _ => fallback_cmp(&self.text, &other.text),
+        }
+    }
+}
+
+/// Split off a leading `v`, as in `v1.2.3`
+fn strip_prefix(text: &str) -> &str {
+    text.strip_prefix(['v', 'V']).unwrap_or(text)
+}
+
+fn numeric(part: &str) -> Option<u64> {
+    (!part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| part.parse().ok())
+        .flatten()
+}
+
+fn parse_semver(text: &str) -> Option<Parsed> {
+    let text = strip_prefix(text);
+    let (text, build) = match text.split_once('+') {
+        Some((text, build)) => (text, Some(build)),
+        None => (text, None),
+    };
+    let (release, pre) = match text.split_once('-') {
+        Some((release, pre)) => (release, Some(pre)),
+        None => (text, None),
+    };
+
+    let identifiers = |s: &str| {
+        s.split('.').all(|part| {
+            !part.is_empty() && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+    };
+    if pre.is_some_and(|pre| !identifiers(pre)) || build.is_some_and(|build| !identifiers(build)) {
+        return None;
+    }
+
+    let parts = release
+        .split('.')
+        .map(numeric)
+        .collect::<Option<Vec<_>>>()?;
+    let release = <[u64; 3]>::try_from(parts).ok()?;
+    Some(Parsed::Semver {
+        release,
+        pre: pre
+            .map(|pre| pre.split('.').map(str::to_string).collect())
+            .unwrap_or_default(),
+    })
+}
+
+fn parse_calver(text: &str) -> Option<Parsed> {
+    let text = strip_prefix(text);
+    let (release, modifier) = match text.split_once('-') {
+        Some((release, modifier)) => (release, Some(modifier)),
+        None => (text, None),
+    };
+
+    let parts = release.split('.').collect::<Vec<_>>();
+    let year = parts.first()?;
+    if year.len() != 4 || !(2..=4).contains(&parts.len()) {
+        return None;
+    }
+    let release = parts
+        .iter()
+        .map(|part| numeric(part))
+        .collect::<Option<Vec<_>>>()?;
+    if release[0] < 1970
+        || release
+            .get(1)
+            .is_some_and(|month| !(1..=12).contains(month))
+    {
+        return None;
+    }
+    Some(Parsed::Calver {
+        release,
+        modifier: modifier
+            .filter(|modifier| !modifier.is_empty())
+            .map(str::to_string),
+    })
+}
+
+fn parse_pep440(text: &str) -> Option<Parsed> {
+    let text = text.trim().to_ascii_lowercase();
+    let text = strip_prefix(&text);
+    let text = text.split_once('+').map_or(text, |(public, _local)| public);
+    let (epoch, text) = match text.split_once('!') {
+        Some((epoch, text)) => (numeric(epoch)?, text),
+        None => (0, text),
+    };
+
+    let end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let release = text[..end].trim_end_matches('.');
+    let mut rest = &text[release.len()..];
+    let release = release
+        .split('.')
+        .map(numeric)
+        .collect::<Option<Vec<_>>>()?;
+
+    let pre = pep440_suffix(
+        &mut rest,
+        &[
+            ("alpha", 0),
+            ("beta", 1),
+            ("preview", 2),
+            ("pre", 2),
+            ("rc", 2),
+            ("a", 0),
+            ("b", 1),
+            ("c", 2),
+        ],
+    );
+    let post = match pep440_suffix(&mut rest, &[("post", 0), ("rev", 0), ("r", 0)]) {
+        Some((_, n)) => Some(n),
+        // The implicit post release form, `1.0-1`
+        None => match rest.strip_prefix('-').and_then(numeric) {
+            Some(n) => {
+                rest = "";
+                Some(n)
+            }
+            None => None,
+        },
+    };
+    let dev = pep440_suffix(&mut rest, &[("dev", 0)]).map(|(_, n)| n);
+
+    rest.is_empty().then_some(Parsed::Pep440 {
+        epoch,
+        release,
+        pre,
+        post,
+        dev,
+    })
+}
+
+/// Take a PEP 440 suffix such as `.post1` from the start of `rest`, with its label's value
+///
+/// Separators are optional, and a missing number means 0.
+fn pep440_suffix(rest: &mut &str, labels: &[(&str, u8)]) -> Option<(u8, u64)> {
+    let trimmed = rest.trim_start_matches(['.', '-', '_']);
+    let (label, value) = labels
+        .iter()
+        .find(|(label, _)| trimmed.starts_with(label))?;
+    let after = trimmed[label.len()..].trim_start_matches(['.', '-', '_']);
+    let digits = after
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(after.len());
+    *rest = &after[digits..];
+    Some((*value, numeric(&after[..digits]).unwrap_or(0)))
+}
+
+/// Sort key following PEP 440, where `.devN` of a final release sorts before its pre-releases
+fn pep440_key(parsed: &Parsed) -> impl Ord {
+    let Parsed::Pep440 {
+        epoch,
+        release,
+        pre,
+        post,
+        dev,
+    } = parsed
+    else {
+        unreachable!("only called for PEP 440 versions");
+    };
+    let mut release = release.clone();
+    while release.len() > 1 && release.last() == Some(&0) {
+        release.pop();
+    }
+    let pre = match (pre, post, dev) {
+        (Some((phase, n)), _, _) => (1, *phase, *n),
+        (None, None, Some(_)) => (0, 0, 0),
+        (None, _, _) => (2, 0, 0),
+    };
+    let post = post.map_or((0, 0), |n| (1, n));
+    let dev = dev.map_or((1, 0), |n| (0, n));
+    (*epoch, release, pre, post, dev)
+}
+
+/// Semver pre-release precedence, where a release is higher than any of its pre-releases
+fn compare_prerelease(a: &[String], b: &[String]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (numeric(a), numeric(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.cmp(b),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Compare numeric components numerically, and everything else as text
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let components =
+        |s| str::split(s, |c: char| !c.is_ascii_alphanumeric()).map(|part| (numeric(part), part));
+    let mut a = components(a);
+    let mut b = components(b);
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some((Some(an), _)), Some((Some(bn), _))) => an.cmp(&bn),
+            (Some((_, a)), Some((_, b))) => a.cmp(b),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+/// Order free-form versions, treating anything after a `-` as a pre-release
+fn fallback_cmp(a: &str, b: &str) -> Ordering {
+    let a = strip_prefix(a);
+    let b = strip_prefix(b);
+    let (a_release, a_pre) = a.split_once('-').map_or((a, None), |(r, p)| (r, Some(p)));
+    let (b_release, b_pre) = b.split_once('-').map_or((b, None), |(r, p)| (r, Some(p)));
+
+    natural_cmp(a_release, b_release).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => natural_cmp(a, b),
+    })
+}
+
+impl PartialEq for VersionNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
+
+impl Eq for VersionNumber {}
+
+impl PartialOrd for VersionNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for VersionNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other)
+            .then_with(|| self.text.cmp(&other.text))
+    }
+}
+
+impl std::fmt::Display for VersionNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl std::str::FromStr for VersionNumber {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(VersionNumber::parse(s))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detects_schemes() {
+        let scheme = |text| VersionNumber::parse(text).scheme();
+        assert_eq!(scheme("1.2.3-rc.1+build.5"), Some(Scheme::Semver));
+        assert_eq!(scheme("v1.2.3"), Some(Scheme::Semver));
+        assert_eq!(scheme("2025.03.1"), Some(Scheme::Calver));
+        assert_eq!(scheme("2025.3"), Some(Scheme::Calver));
+        assert_eq!(scheme("1.2"), None);
+        assert_eq!(scheme("Initial release"), None);
+        assert!(VersionNumber::parse("2025.03.1-beta").is_prerelease());
+        assert!(!VersionNumber::parse("1.2.3+build").is_prerelease());
+
+        assert!(VersionNumber::parse_as("1.2", Scheme::Semver).is_err());
+        assert!(VersionNumber::parse_as("25.1", Scheme::Calver).is_err());
+        let pep440 = VersionNumber::parse_as("1.0RC1.post2", Scheme::Pep440).unwrap();
+        assert!(pep440.is_prerelease());
+        assert!(VersionNumber::parse_as("1.0-final", Scheme::Pep440).is_err());
+    }
+
+    #[test]
+    fn orders_pep440() {
+        let ordered = [
+            "1.0.dev1",
+            "1.0a1",
+            "1.0a2.dev1",
+            "1.0a2",
+            "1.0b1",
+            "1.0rc1",
+            "1.0",
+            "1.0.post1",
+            "1.1",
+            "1!0.1",
+        ];
+        for pair in ordered.windows(2) {
+            let a = VersionNumber::parse_as(pair[0], Scheme::Pep440).unwrap();
+            let b = VersionNumber::parse_as(pair[1], Scheme::Pep440).unwrap();
+            assert!(a < b, "{} < {}", a, b);
+        }
+        let a = VersionNumber::parse_as("1.0", Scheme::Pep440).unwrap();
+        let b = VersionNumber::parse_as("1.0.0", Scheme::Pep440).unwrap();
+        assert_eq!(a.cmp_precedence(&b), Ordering::Equal);
+    }
+
+    #[test]
+    fn bumps_versions() {
+        let bump = |text: &str, bump| VersionNumber::parse(text).bump(bump).unwrap().to_string();
+        assert_eq!(bump("v1.2.3", Bump::Patch), "v1.2.4");
+        assert_eq!(bump("1.2.3", Bump::Minor), "1.3.0");
+        assert_eq!(bump("1.2.3", Bump::Major), "2.0.0");
+        assert_eq!(bump("2.0.0-rc.1", Bump::Major), "2.0.0");
+        assert_eq!(bump("1.3.0-beta", Bump::Patch), "1.3.0");
+        assert!(
+            VersionNumber::parse("2025.03.1")
+                .bump(Bump::Minor)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn orders_by_precedence() {
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.10.0",
+            "2.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            let (a, b) = (VersionNumber::parse(pair[0]), VersionNumber::parse(pair[1]));
+            assert!(a < b, "{} < {}", a, b);
+        }
+        assert!(VersionNumber::parse("2025.03") < VersionNumber::parse("2025.03.1"));
+        assert!(VersionNumber::parse("2025.10.0") > VersionNumber::parse("2025.9.1"));
+        assert_eq!(
+            VersionNumber::parse("v1.0.0+build").cmp_precedence(&VersionNumber::parse("1.0.0")),
+            Ordering::Equal
+        );
+    }
+}